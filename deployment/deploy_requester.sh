@@ -4,7 +4,7 @@
 network=${network:-testnet}
 accountId=${accountId:-oracle.account.testnet}
 oracle=${oracle:-flux-dev}
-stakeToken=${stakeToken:-v2.wnear.flux-dev}
+paymentToken=${paymentToken:-v2.wnear.flux-dev}
 
 while [ $# -gt 0 ]; do
 
@@ -17,4 +17,4 @@ while [ $# -gt 0 ]; do
   shift
 done
 
-NEAR_ENV=$network near deploy --accountId $accountId --wasmFile ./res/request_interface.wasm --initFunction new --initArgs '{"oracle": "'$oracle'", "stake_token": "'$stakeToken'"}'
+NEAR_ENV=$network near deploy --accountId $accountId --wasmFile ./res/request_interface.wasm --initFunction new --initArgs '{"oracle": "'$oracle'", "payment_token": "'$paymentToken'", "whitelist": ["'$accountId'"]}'
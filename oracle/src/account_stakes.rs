@@ -0,0 +1,310 @@
+use crate::*;
+use flux_sdk::{outcome::Outcome, types::WrappedBalance};
+use near_sdk::{
+    collections::UnorderedMap,
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+};
+
+/// Amount an account has staked on an outcome in a resolution window
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountStake {
+    pub round: u16,
+    pub outcome: Outcome,
+    pub amount: WrappedBalance,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountStakesSummary {
+    pub request_id: U64,
+    pub stakes: Vec<AccountStake>,
+}
+
+impl Contract {
+    // @returns map of data request id to the stakes `account_id` has in its resolution windows
+    fn stakes_of(&self, account_id: &AccountId) -> UnorderedMap<u64, Vec<AccountStake>> {
+        self.account_stakes.get(account_id).unwrap_or_else(|| {
+            // the trailing separator keeps the prefix of one account from being the start of another account's prefix
+            UnorderedMap::new(format!("as:{}:", account_id).as_bytes().to_vec())
+        })
+    }
+
+    /**
+     * @notice Sets the amount `account_id` has staked on `outcome` in round `round` of data request `dr_id`,
+     * an amount of 0 removes the stake from the index
+     */
+    pub fn set_account_stake(
+        &mut self,
+        account_id: &AccountId,
+        dr_id: u64,
+        round: u16,
+        outcome: &Outcome,
+        amount: Balance,
+    ) {
+        self.update_account_stake(account_id, dr_id, round, outcome, amount, true);
+    }
+
+    /**
+     * @notice Lowers the indexed stake of `account_id` on `outcome` in round `round` of data request `dr_id` to `amount`.
+     * Stake that is no longer indexed because the request got claimed is left out, unstaking what's left of it doesn't
+     * add it back
+     */
+    pub fn reduce_account_stake(
+        &mut self,
+        account_id: &AccountId,
+        dr_id: u64,
+        round: u16,
+        outcome: &Outcome,
+        amount: Balance,
+    ) {
+        self.update_account_stake(account_id, dr_id, round, outcome, amount, false);
+    }
+
+    fn update_account_stake(
+        &mut self,
+        account_id: &AccountId,
+        dr_id: u64,
+        round: u16,
+        outcome: &Outcome,
+        amount: Balance,
+        insert_missing: bool,
+    ) {
+        let mut stakes_by_request = self.stakes_of(account_id);
+        let mut stakes = stakes_by_request.get(&dr_id).unwrap_or_default();
+        let index = stakes
+            .iter()
+            .position(|stake| stake.round == round && &stake.outcome == outcome);
+
+        match (index, amount) {
+            (Some(i), 0) => {
+                stakes.remove(i);
+            }
+            (Some(i), _) => stakes[i].amount = U128(amount),
+            (None, 0) => return,
+            (None, _) if !insert_missing => return,
+            (None, _) => stakes.push(AccountStake {
+                round,
+                outcome: outcome.clone(),
+                amount: U128(amount),
+            }),
+        };
+
+        if stakes.is_empty() {
+            stakes_by_request.remove(&dr_id);
+        } else {
+            stakes_by_request.insert(&dr_id, &stakes);
+        }
+        self.account_stakes.insert(account_id, &stakes_by_request);
+    }
+
+    /**
+     * @notice Removes all stakes of `account_id` in data request `dr_id` from the index once it claimed, whatever the
+     * outcome they are on. Stake on the final outcome got paid out, stake bonded on other outcomes is lost and stake in
+     * rounds that never got bonded on it, like the last round, is no longer followed
+     */
+    pub fn remove_claimed_stakes(&mut self, account_id: &AccountId, dr_id: u64) {
+        let mut stakes_by_request = self.stakes_of(account_id);
+        if stakes_by_request.remove(&dr_id).is_some() {
+            self.account_stakes.insert(account_id, &stakes_by_request);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * @returns the stakes of `account_id` grouped per data request, paginated over the data requests it staked on
+     */
+    pub fn get_account_stakes(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: U64,
+    ) -> Vec<AccountStakesSummary> {
+        let stakes_by_request = self.stakes_of(&account_id);
+        let request_ids = stakes_by_request.keys_as_vector();
+        let stakes = stakes_by_request.values_as_vector();
+        let i: u64 = from_index.into();
+
+        (i..std::cmp::min(i + u64::from(limit), request_ids.len()))
+            .map(|index| AccountStakesSummary {
+                request_id: U64(request_ids.get(index).unwrap()),
+                stakes: stakes.get(index).unwrap(),
+            })
+            .collect()
+    }
+
+    /**
+     * @returns ids of the finalized data requests in the same page as `get_account_stakes` on which `account_id`
+     * still has a payout to claim
+     */
+    pub fn get_account_unclaimed_requests(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: U64,
+    ) -> Vec<U64> {
+        let stakes_by_request = self.stakes_of(&account_id);
+        let request_ids = stakes_by_request.keys_as_vector();
+        let i: u64 = from_index.into();
+
+        (i..std::cmp::min(i + u64::from(limit), request_ids.len()))
+            .map(|index| request_ids.get(index).unwrap())
+            .map(U64)
+            .filter(
+                |request_id| match self.get_claimable(account_id.to_string(), *request_id) {
+                    Some(claimable) => {
+                        claimable.stake_token_payout.0 > 0 || claimable.payment_token_payout.0 > 0
+                    }
+                    None => false,
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use crate::test_utils::*;
+    use flux_sdk::{
+        data_request::{DataRequestDataType, NewDataRequestArgs, StakeDataRequestArgs},
+        outcome::AnswerType,
+    };
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    fn outcome(answer: &str) -> Outcome {
+        Outcome::Answer(AnswerType::String(answer.to_string()))
+    }
+
+    fn dr_new(contract: &mut Contract) {
+        contract.dr_new(
+            bob(),
+            100,
+            NewDataRequestArgs {
+                sources: Some(Vec::new()),
+                outcomes: Some(vec!["a".to_string(), "b".to_string()]),
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
+                data_type: DataRequestDataType::String,
+                provider: None,
+            },
+        );
+    }
+
+    fn stake(contract: &mut Contract, account_id: AccountId, id: u64, answer: &str, amount: u128) {
+        contract.dr_stake(
+            account_id,
+            amount,
+            StakeDataRequestArgs {
+                id: U64(id),
+                outcome: outcome(answer),
+            },
+        );
+    }
+
+    #[test]
+    fn account_stakes_follow_stake_and_unstake() {
+        testing_env!(get_context_at(token(), 0));
+        let whitelist = Some(vec![registry_entry(bob())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+
+        stake(&mut contract, alice(), 0, "a", 50);
+        stake(&mut contract, alice(), 0, "a", 25);
+        stake(&mut contract, alice(), 1, "b", 10);
+
+        assert_eq!(
+            contract.get_account_stakes(alice(), U64(0), U64(10)),
+            vec![
+                AccountStakesSummary {
+                    request_id: U64(0),
+                    stakes: vec![AccountStake {
+                        round: 0,
+                        outcome: outcome("a"),
+                        amount: U128(75),
+                    }],
+                },
+                AccountStakesSummary {
+                    request_id: U64(1),
+                    stakes: vec![AccountStake {
+                        round: 0,
+                        outcome: outcome("b"),
+                        amount: U128(10),
+                    }],
+                },
+            ]
+        );
+        assert_eq!(
+            contract.get_account_stakes(alice(), U64(1), U64(10)).len(),
+            1
+        );
+        assert!(contract
+            .get_account_stakes(bob(), U64(0), U64(10))
+            .is_empty());
+
+        testing_env!(get_context_at(alice(), 0));
+        contract.dr_unstake(U64(1), 0, outcome("b"), U128(10));
+        contract.dr_unstake(U64(0), 0, outcome("a"), U128(5));
+
+        let stakes = contract.get_account_stakes(alice(), U64(0), U64(10));
+        assert_eq!(stakes.len(), 1);
+        assert_eq!(stakes[0].stakes[0].amount, U128(70));
+    }
+
+    #[test]
+    fn account_unclaimed_requests() {
+        testing_env!(get_context_at(token(), 0));
+        let whitelist = Some(vec![registry_entry(bob())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+
+        // bonds round 0 of both requests
+        stake(&mut contract, alice(), 0, "a", 200);
+        stake(&mut contract, alice(), 1, "a", 200);
+        // challenges round 0 of request 0 without bonding round 1
+        stake(&mut contract, alice(), 0, "b", 10);
+        assert!(contract
+            .get_account_unclaimed_requests(alice(), U64(0), U64(10))
+            .is_empty());
+
+        testing_env!(get_context_at(token(), 1501));
+        contract.dr_finalize(U64(0));
+
+        assert_eq!(
+            contract.get_account_unclaimed_requests(alice(), U64(0), U64(10)),
+            vec![U64(0)]
+        );
+
+        contract.dr_claim(alice(), U64(0));
+        assert!(contract
+            .get_account_unclaimed_requests(alice(), U64(0), U64(10))
+            .is_empty());
+        // claiming removes all stake in the request from the index, also the stake on "b" in the unbonded round
+        assert_eq!(
+            contract.get_account_stakes(alice(), U64(0), U64(10)),
+            vec![AccountStakesSummary {
+                request_id: U64(1),
+                stakes: vec![AccountStake {
+                    round: 0,
+                    outcome: outcome("a"),
+                    amount: U128(200),
+                }],
+            }]
+        );
+
+        // unstaking what's left of the unbonded stake doesn't add it back
+        testing_env!(get_context_at(alice(), 1501));
+        contract.dr_unstake(U64(0), 1, outcome("b"), U128(5));
+        assert_eq!(
+            contract.get_account_stakes(alice(), U64(0), U64(10)).len(),
+            1
+        );
+    }
+}
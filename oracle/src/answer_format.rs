@@ -0,0 +1,197 @@
+use crate::*;
+use flux_sdk::{
+    data_request::DataRequestDataType,
+    outcome::{AnswerType, Outcome},
+};
+use near_sdk::{
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+};
+
+pub const TRUE_ANSWER: &str = "true";
+pub const FALSE_ANSWER: &str = "false";
+/// Separator of the outcome indices in a multi-select answer
+pub const SELECTION_SEPARATOR: char = ',';
+
+/**
+ * Formats of string answers on top of the plain strings of `DataRequestDataType::String`. Answers are strings in a
+ * single canonical form so that two equal answers are always the same `Outcome`
+ */
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum AnswerFormat {
+    // indices of the selected `outcomes` in ascending order separated by a comma, e.g. "0,2,3", "" selects none
+    MultiSelect,
+    // either "true" or "false", the request has no `outcomes`
+    Boolean,
+    // lowercase hex encoded sha256 digest of a payload too large to stake on, the request has no `outcomes`
+    PayloadHash,
+}
+
+impl AnswerFormat {
+    pub fn assert_valid(&self, data_type: &DataRequestDataType, outcomes: &Option<Vec<String>>) {
+        assert_eq!(
+            data_type,
+            &DataRequestDataType::String,
+            "answer formats can only be set on string requests"
+        );
+        match self {
+            AnswerFormat::MultiSelect => assert!(
+                outcomes.is_some(),
+                "multi-select requests need outcomes to select from"
+            ),
+            AnswerFormat::Boolean => {
+                assert!(outcomes.is_none(), "boolean requests can't have outcomes")
+            }
+            AnswerFormat::PayloadHash => assert!(
+                outcomes.is_none(),
+                "payload hash requests can't have outcomes"
+            ),
+        }
+    }
+
+    pub fn assert_valid_answer(&self, answer: &str, outcomes: Option<&Vec<String>>) {
+        match self {
+            AnswerFormat::MultiSelect => {
+                let outcome_count = outcomes.map(|outcomes| outcomes.len()).unwrap_or(0);
+                let selection = parse_selection(answer).expect("ERR_INVALID_SELECTION");
+                assert!(
+                    selection.iter().all(|index| *index < outcome_count),
+                    "ERR_SELECTION_OUT_OF_RANGE"
+                );
+            }
+            AnswerFormat::Boolean => assert!(
+                answer == TRUE_ANSWER || answer == FALSE_ANSWER,
+                "ERR_OUTCOME_NOT_BOOLEAN"
+            ),
+            AnswerFormat::PayloadHash => {
+                assert!(payload_reveal::is_digest(answer), "ERR_OUTCOME_NOT_DIGEST")
+            }
+        }
+    }
+}
+
+/**
+ * @notice Parses a multi-select answer
+ * @returns the selected outcome indices, `None` if `answer` is not in canonical form
+ */
+pub fn parse_selection(answer: &str) -> Option<Vec<usize>> {
+    if answer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut selection: Vec<usize> = Vec::new();
+    for part in answer.split(SELECTION_SEPARATOR) {
+        let index: usize = part.parse().ok()?;
+        // rejects leading zeros, signs and whitespace
+        if index.to_string() != part {
+            return None;
+        }
+        if let Some(prev) = selection.last() {
+            if index <= *prev {
+                return None;
+            }
+        }
+        selection.push(index);
+    }
+
+    Some(selection)
+}
+
+impl Contract {
+    /**
+     * @notice Checks `outcome` against the answer format of data request `dr_id`
+     * @returns whether the data request has an answer format, if not its answers are checked against its `outcomes`
+     */
+    pub fn assert_valid_answer_format(
+        &self,
+        dr_id: u64,
+        outcome: &Outcome,
+        outcomes: Option<&Vec<String>>,
+    ) -> bool {
+        let format = match self.answer_formats.get(&dr_id) {
+            Some(format) => format,
+            None => return false,
+        };
+
+        match outcome {
+            Outcome::Answer(AnswerType::String(answer)) => {
+                format.assert_valid_answer(answer, outcomes)
+            }
+            Outcome::Answer(_) => panic!("ERR_OUTCOME_NOT_STRING"),
+            Outcome::Invalid => (),
+        }
+        true
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_answer_format(&self, request_id: U64) -> Option<AnswerFormat> {
+        self.answer_formats.get(&request_id.into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+
+    fn outcomes() -> Vec<String> {
+        vec!["a".to_string(), "b".to_string(), "c".to_string()]
+    }
+
+    #[test]
+    fn selection_parse() {
+        assert_eq!(parse_selection(""), Some(vec![]));
+        assert_eq!(parse_selection("0,2"), Some(vec![0, 2]));
+        assert_eq!(parse_selection("2,0"), None);
+        assert_eq!(parse_selection("0,0"), None);
+        assert_eq!(parse_selection("01"), None);
+        assert_eq!(parse_selection("0, 2"), None);
+        assert_eq!(parse_selection("0,"), None);
+    }
+
+    #[test]
+    fn multi_select_valid_answer() {
+        AnswerFormat::MultiSelect.assert_valid_answer("0,1,2", Some(&outcomes()));
+        AnswerFormat::MultiSelect.assert_valid_answer("", Some(&outcomes()));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_SELECTION_OUT_OF_RANGE")]
+    fn multi_select_out_of_range() {
+        AnswerFormat::MultiSelect.assert_valid_answer("1,3", Some(&outcomes()));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_SELECTION")]
+    fn multi_select_not_canonical() {
+        AnswerFormat::MultiSelect.assert_valid_answer("1,0", Some(&outcomes()));
+    }
+
+    #[test]
+    fn boolean_valid_answer() {
+        AnswerFormat::Boolean.assert_valid_answer(TRUE_ANSWER, None);
+        AnswerFormat::Boolean.assert_valid_answer(FALSE_ANSWER, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_OUTCOME_NOT_BOOLEAN")]
+    fn boolean_invalid_answer() {
+        AnswerFormat::Boolean.assert_valid_answer("yes", None);
+    }
+
+    #[test]
+    #[should_panic(expected = "boolean requests can't have outcomes")]
+    fn boolean_with_outcomes() {
+        AnswerFormat::Boolean.assert_valid(&DataRequestDataType::String, &Some(outcomes()));
+    }
+
+    #[test]
+    #[should_panic(expected = "answer formats can only be set on string requests")]
+    fn multi_select_on_number_request() {
+        AnswerFormat::MultiSelect
+            .assert_valid(&DataRequestDataType::Number(U128(100)), &Some(outcomes()));
+    }
+}
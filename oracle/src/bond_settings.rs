@@ -0,0 +1,199 @@
+use crate::*;
+use flux_sdk::types::WrappedBalance;
+use near_sdk::{
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+};
+
+/// Denominator of bond growth factors, e.g. 20000 == 2x
+pub const BOND_GROWTH_DIVISOR: u32 = 10_000;
+/// Factor by which the bond grows each round if the requester did not set one
+pub const DEFAULT_BOND_GROWTH: u32 = 2 * BOND_GROWTH_DIVISOR;
+
+/// Bond parameters a requester can set on a new data request, any setting left out uses the default rule
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BondSettings {
+    // replaces max(fee, validity bond, min resolution bond) as the bond the first round grows from
+    pub initial_bond: Option<WrappedBalance>,
+    // factor the bond grows by each round
+    pub bond_growth: Option<u32>,
+    // duration of every round after the first
+    pub challenge_duration: Option<U64>,
+}
+
+/// Bounds set by governance for the `BondSettings` requesters can choose
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BondLimits {
+    pub min_initial_bond: WrappedBalance,
+    pub max_initial_bond: WrappedBalance,
+    pub min_bond_growth: u32,
+    pub max_bond_growth: u32,
+    pub min_challenge_duration: U64,
+    pub max_challenge_duration: U64,
+}
+
+impl Default for BondLimits {
+    fn default() -> Self {
+        Self {
+            min_initial_bond: U128(0),
+            max_initial_bond: U128(u128::MAX),
+            min_bond_growth: BOND_GROWTH_DIVISOR * 3 / 2,
+            max_bond_growth: 4 * BOND_GROWTH_DIVISOR,
+            min_challenge_duration: U64(60 * 60 * 1_000_000_000), // 1 hour
+            max_challenge_duration: U64(7 * 24 * 60 * 60 * 1_000_000_000), // 7 days
+        }
+    }
+}
+
+impl BondLimits {
+    pub fn assert_valid(&self) {
+        assert!(
+            u128::from(self.min_initial_bond) <= u128::from(self.max_initial_bond),
+            "min initial bond can't be higher than max initial bond"
+        );
+        assert!(
+            self.min_bond_growth >= BOND_GROWTH_DIVISOR,
+            "min bond growth can't be lower than {}",
+            BOND_GROWTH_DIVISOR
+        );
+        assert!(
+            self.min_bond_growth <= self.max_bond_growth,
+            "min bond growth can't be higher than max bond growth"
+        );
+        assert!(
+            u64::from(self.min_challenge_duration) <= u64::from(self.max_challenge_duration),
+            "min challenge duration can't be higher than max challenge duration"
+        );
+    }
+}
+
+impl BondSettings {
+    pub fn assert_within(&self, limits: &BondLimits, min_resolution_bond: Balance) {
+        if let Some(initial_bond) = self.initial_bond {
+            let initial_bond: u128 = initial_bond.into();
+            let min = std::cmp::max(u128::from(limits.min_initial_bond), min_resolution_bond);
+            let max: u128 = limits.max_initial_bond.into();
+            assert!(
+                initial_bond >= min && initial_bond <= max,
+                "initial bond has to be between {} and {}",
+                min,
+                max
+            );
+        }
+        if let Some(bond_growth) = self.bond_growth {
+            assert!(
+                bond_growth >= limits.min_bond_growth && bond_growth <= limits.max_bond_growth,
+                "bond growth has to be between {} and {}",
+                limits.min_bond_growth,
+                limits.max_bond_growth
+            );
+        }
+        if let Some(challenge_duration) = self.challenge_duration {
+            let challenge_duration: u64 = challenge_duration.into();
+            assert!(
+                challenge_duration >= limits.min_challenge_duration.into()
+                    && challenge_duration <= limits.max_challenge_duration.into(),
+                "challenge duration has to be between {} and {}",
+                u64::from(limits.min_challenge_duration),
+                u64::from(limits.max_challenge_duration)
+            );
+        }
+    }
+}
+
+impl Contract {
+    // @returns the factor the bond of data request `dr_id` grows by each round
+    pub fn bond_growth(&self, dr_id: u64) -> u32 {
+        self.bond_settings
+            .get(&dr_id)
+            .and_then(|settings| settings.bond_growth)
+            .unwrap_or(DEFAULT_BOND_GROWTH)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_bond_settings(&self, request_id: U64) -> Option<BondSettings> {
+        self.bond_settings.get(&request_id.into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+
+    fn settings() -> BondSettings {
+        BondSettings {
+            initial_bond: Some(U128(500)),
+            bond_growth: Some(30_000),
+            challenge_duration: Some(U64(2 * 60 * 60 * 1_000_000_000)),
+        }
+    }
+
+    #[test]
+    fn settings_within_limits() {
+        settings().assert_within(&BondLimits::default(), 100);
+        BondSettings {
+            initial_bond: None,
+            bond_growth: None,
+            challenge_duration: None,
+        }
+        .assert_within(&BondLimits::default(), 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "initial bond has to be between 100 and")]
+    fn settings_initial_bond_below_min_resolution_bond() {
+        BondSettings {
+            initial_bond: Some(U128(99)),
+            ..settings()
+        }
+        .assert_within(&BondLimits::default(), 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "bond growth has to be between 15000 and 40000")]
+    fn settings_bond_growth_exceed() {
+        BondSettings {
+            bond_growth: Some(40_001),
+            ..settings()
+        }
+        .assert_within(&BondLimits::default(), 100);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "challenge duration has to be between 3600000000000 and 604800000000000"
+    )]
+    fn settings_challenge_duration_below_min() {
+        BondSettings {
+            challenge_duration: Some(U64(1000)),
+            ..settings()
+        }
+        .assert_within(&BondLimits::default(), 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "min bond growth can't be lower than 10000")]
+    fn limits_shrinking_bond() {
+        BondLimits {
+            min_bond_growth: 9_999,
+            ..BondLimits::default()
+        }
+        .assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = "min challenge duration can't be higher than max challenge duration")]
+    fn limits_inverted_duration() {
+        BondLimits {
+            min_challenge_duration: U64(2),
+            max_challenge_duration: U64(1),
+            ..BondLimits::default()
+        }
+        .assert_valid();
+    }
+}
@@ -1,3 +1,4 @@
+use crate::data_request::DataRequestSettings;
 use crate::*;
 use flux_sdk::{
     consts::{MAX_SOURCES, MAX_TAGS, MIN_OUTCOMES, MIN_PERIOD_MULTIPLIER},
@@ -48,4 +49,41 @@ impl Contract {
             config.max_outcomes
         );
     }
+    // panics if `settings` can't be set on a data request created with `data_request`
+    pub fn dr_validate_settings(
+        &self,
+        data_request: &NewDataRequestArgs,
+        settings: &DataRequestSettings,
+    ) {
+        if let Some(bonds) = &settings.bond_settings {
+            bonds.assert_within(
+                &self.extended_config.bond_limits,
+                self.get_config().min_resolution_bond.into(),
+            );
+        }
+        if let Some(bounds) = &settings.numeric_bounds {
+            bounds.assert_valid(&data_request.data_type);
+        }
+        if let Some(format) = &settings.answer_format {
+            format.assert_valid(&data_request.data_type, &data_request.outcomes);
+        }
+        if let Some(commit_reveal) = &settings.commit_reveal {
+            commit_reveal.assert_valid();
+            assert!(
+                data_request.provider.is_none(),
+                "provider requests can't be staked through commits"
+            );
+        }
+        if let Some(quorum) = &settings.provider_quorum {
+            quorum.assert_valid();
+            assert!(
+                data_request.provider.is_none(),
+                "provider requests can't have a provider quorum"
+            );
+            assert!(
+                settings.commit_reveal.is_none(),
+                "requests with a provider quorum can't be staked through commits"
+            );
+        }
+    }
 }
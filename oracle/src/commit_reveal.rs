@@ -0,0 +1,211 @@
+use crate::*;
+use flux_sdk::{outcome::Outcome, types::WrappedBalance};
+use near_sdk::{
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+    serde_json,
+};
+
+/// Maximum number of stake commits on a data request, bounds the gas of settling them in one call
+pub const MAX_COMMITS: usize = 32;
+/// Divisor of `ExtendedOracleConfig::min_commit_share`
+pub const MIN_COMMIT_SHARE_DIVISOR: u32 = 10_000;
+/// Minimum commit by default, filling every commit slot costs about the first round bond
+pub const DEFAULT_MIN_COMMIT_SHARE: u32 = MIN_COMMIT_SHARE_DIVISOR / MAX_COMMITS as u32;
+
+/// Durations of the phases of a data request of which the first round is staked through commits
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CommitRevealSettings {
+    pub commit_duration: U64, // time after creation in which stakes can be committed
+    pub reveal_duration: U64, // time after the commit phase in which committed stakes can be revealed
+}
+
+impl CommitRevealSettings {
+    pub fn assert_valid(&self) {
+        assert!(
+            u64::from(self.commit_duration) > 0,
+            "commit duration has to be higher than 0"
+        );
+        assert!(
+            u64::from(self.reveal_duration) > 0,
+            "reveal duration has to be higher than 0"
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CommitStakeArgs {
+    pub id: U64,
+    pub commitment: String, // see `commitment`, only valid for the account and data request it was made for
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakeCommit {
+    pub account_id: AccountId,
+    pub commitment: String,
+    pub amount: WrappedBalance,
+    pub revealed: Option<Outcome>,
+}
+
+/// Stake commits on the first round of a data request, only applied to its first resolution window once settled
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CommitRound {
+    pub commit_end: U64,
+    pub reveal_end: U64,
+    pub commits: Vec<StakeCommit>,
+    pub settled: bool,
+}
+
+impl CommitRound {
+    /**
+     * @notice Orders the revealed commits for settlement, the outcome with the most revealed stake first. On a tie
+     * the outcome committed on first goes first
+     * @returns indices of the revealed commits in settlement order
+     */
+    pub fn settlement_order(&self) -> Vec<usize> {
+        let mut totals: Vec<(Outcome, Balance)> = Vec::new();
+        for commit in self.commits.iter() {
+            if let Some(outcome) = &commit.revealed {
+                match totals
+                    .iter_mut()
+                    .find(|(total_outcome, _)| total_outcome == outcome)
+                {
+                    Some((_, total)) => *total += u128::from(commit.amount),
+                    None => totals.push((outcome.clone(), commit.amount.into())),
+                }
+            }
+        }
+
+        let mut leading: Option<&(Outcome, Balance)> = None;
+        for total in totals.iter() {
+            if leading.map(|(_, amount)| total.1 > *amount).unwrap_or(true) {
+                leading = Some(total);
+            }
+        }
+
+        let revealed: Vec<usize> = (0..self.commits.len())
+            .filter(|i| self.commits[*i].revealed.is_some())
+            .collect();
+        match leading {
+            Some((leading_outcome, _)) => {
+                let (mut first, rest): (Vec<usize>, Vec<usize>) = revealed
+                    .into_iter()
+                    .partition(|i| self.commits[*i].revealed.as_ref() == Some(leading_outcome));
+                first.extend(rest);
+                first
+            }
+            None => revealed,
+        }
+    }
+}
+
+/**
+ * @returns the lowercase hex encoded sha256 digest of the json array `[dr_id, account_id, outcome, salt]`. Binding
+ * the commitment to the account and data request keeps others from copying it and revealing it as their own once
+ * the committer revealed. Stakers compute it client-side, there is no view for it since sending the outcome and
+ * salt to an RPC node would expose them before the reveal phase
+ */
+pub fn commitment(dr_id: u64, account_id: &AccountId, outcome: &Outcome, salt: &str) -> String {
+    let preimage = serde_json::to_string(&(U64(dr_id), account_id, outcome, salt)).unwrap();
+    helpers::sha256_hex(preimage.as_bytes())
+}
+
+impl Contract {
+    // @notice opens the commit phase of a data request created at the current block
+    pub fn init_commit_round(&mut self, dr_id: u64, settings: &CommitRevealSettings) {
+        let commit_end = env::block_timestamp() + u64::from(settings.commit_duration);
+        self.commit_rounds.insert(
+            &dr_id,
+            &CommitRound {
+                commit_end: U64(commit_end),
+                reveal_end: U64(commit_end + u64::from(settings.reveal_duration)),
+                commits: Vec::new(),
+                settled: false,
+            },
+        );
+    }
+
+    // @returns whether stake is held in commits that have not been settled yet
+    pub fn has_open_commits(&self, dr_id: u64) -> bool {
+        self.commit_rounds
+            .get(&dr_id)
+            .map(|round| !round.settled && !round.commits.is_empty())
+            .unwrap_or(false)
+    }
+
+    // panics if the first round of data request `dr_id` is still staked through commits
+    pub fn assert_commits_settled(&self, dr_id: u64) {
+        if let Some(round) = self.commit_rounds.get(&dr_id) {
+            assert!(
+                round.settled,
+                "Error DataRequest is staked through commits until they are settled"
+            );
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_commit_round(&self, request_id: U64) -> Option<CommitRound> {
+        self.commit_rounds.get(&request_id.into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use flux_sdk::outcome::AnswerType;
+
+    fn answer(answer: &str) -> Outcome {
+        Outcome::Answer(AnswerType::String(answer.to_string()))
+    }
+
+    fn commit(amount: u128, revealed: Option<Outcome>) -> StakeCommit {
+        StakeCommit {
+            account_id: "alice.near".to_string(),
+            commitment: "".to_string(),
+            amount: U128(amount),
+            revealed,
+        }
+    }
+
+    fn round(commits: Vec<StakeCommit>) -> CommitRound {
+        CommitRound {
+            commit_end: U64(0),
+            reveal_end: U64(0),
+            commits,
+            settled: false,
+        }
+    }
+
+    #[test]
+    fn settlement_order_leading_first() {
+        let round = round(vec![
+            commit(100, Some(answer("a"))),
+            commit(500, None),
+            commit(150, Some(answer("b"))),
+            commit(10, Some(answer("a"))),
+        ]);
+        assert_eq!(round.settlement_order(), vec![2, 0, 3]);
+    }
+
+    #[test]
+    fn settlement_order_tie() {
+        let round = round(vec![
+            commit(100, Some(answer("a"))),
+            commit(100, Some(answer("b"))),
+        ]);
+        assert_eq!(round.settlement_order(), vec![0, 1]);
+    }
+
+    #[test]
+    fn settlement_order_nothing_revealed() {
+        let round = round(vec![commit(100, None)]);
+        assert!(round.settlement_order().is_empty());
+    }
+}
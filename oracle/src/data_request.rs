@@ -1,8 +1,18 @@
 use crate::*;
 
 use crate::{
-    fungible_token::fungible_token_transfer, helpers::multiply_stake, logger,
-    requester_handler::RequesterHandler, resolution_window::ResolutionWindowHandler,
+    answer_format::AnswerFormat,
+    bond_settings::{BondSettings, BOND_GROWTH_DIVISOR},
+    commit_reveal::{self, CommitRevealSettings, CommitStakeArgs},
+    failed_payouts::GAS_BASE_PAYOUT,
+    fee_top_up::TopUpDataRequestArgs,
+    fungible_token::{fungible_token_transfer, GAS_BASE_RESOLVE_TRANSFER},
+    helpers::multiply_stake,
+    logger,
+    numeric_bounds::NumericBounds,
+    payload_reveal,
+    provider_quorum::{ProviderQuorumSettings, ProviderSubmission},
+    resolution_window::ResolutionWindowHandler,
 };
 use flux_sdk::{
     config::OracleConfig,
@@ -20,22 +30,64 @@ use near_sdk::{
     collections::Vector,
     env, ext_contract,
     json_types::{U128, U64},
-    AccountId, Balance, Promise, PromiseOrValue,
+    serde::{Deserialize, Serialize},
+    AccountId, Balance, Gas, Promise, PromiseOrValue,
 };
 
+/// Payout `dr_claim` would make to an account on a finalized data request
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimableSummary {
+    pub request_id: U64,
+    pub payment_token_payout: WrappedBalance,
+    pub stake_token_payout: WrappedBalance,
+}
+
+/// Optional settings of a new data request that `NewDataRequestArgs` has no fields for
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DataRequestSettings {
+    pub bond_settings: Option<BondSettings>,
+    pub numeric_bounds: Option<NumericBounds>,
+    pub answer_format: Option<AnswerFormat>,
+    pub commit_reveal: Option<CommitRevealSettings>,
+    pub provider_quorum: Option<ProviderQuorumSettings>,
+}
+
+/// Gas reserved for claiming a single data request in `dr_claim_many`
+pub const GAS_BASE_CLAIM: Gas = 10_000_000_000_000;
+
 #[ext_contract]
 trait ExtSelf {
     fn dr_proceed_finalization(request_id: U64, sender: AccountId);
+    fn dr_unstake_callback(
+        request_id: U64,
+        resolution_round: u16,
+        account_id: AccountId,
+        outcome: Outcome,
+        amount: U128,
+    ) -> bool;
+    fn dr_resolve_claim_many(unclaimed: Vec<U64>) -> Vec<U64>;
 }
 
 trait DataRequestMethods {
     fn unstake(
         &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        round: u16,
+        outcome: Outcome,
+        amount: Balance,
+    ) -> Balance;
+    fn restore_stake(
+        &mut self,
+        contract: &mut Contract,
         sender: AccountId,
         round: u16,
         outcome: Outcome,
         amount: Balance,
     ) -> Balance;
+    fn get_resolution_window(&self, round: u16) -> ResolutionWindow;
     fn get_config_id(&self) -> u64;
     fn log_update(&self);
     fn summarize(&self) -> DataRequestSummary;
@@ -45,21 +97,75 @@ impl DataRequestMethods for DataRequest {
     // @returns amount of tokens that didn't get staked
     fn unstake(
         &mut self,
+        contract: &mut Contract,
         sender: AccountId,
         round: u16,
         outcome: Outcome,
         amount: Balance,
     ) -> Balance {
         let resolution_windows = match self {
-            DataRequest::Active(dr) => &dr.resolution_windows,
-            DataRequest::Finalized(dr) => &dr.resolution_windows,
+            DataRequest::Active(dr) => &mut dr.resolution_windows,
+            DataRequest::Finalized(dr) => &mut dr.resolution_windows,
         };
 
         let mut window = resolution_windows
             .get(round as u64)
             .expect("ERR_NO_RESOLUTION_WINDOW");
 
-        window.unstake(sender, outcome, amount)
+        let unstaked = window.unstake(contract, sender, outcome, amount);
+        resolution_windows.replace(round as u64, &window);
+
+        unstaked
+    }
+
+    /**
+     * @notice reverts an unstake of which the token transfer failed, up to the stake the window still has open. If the
+     * window bonded in the meantime nothing is restored, if the restored stake fills an active round's bond that
+     * round gets bonded and the next one opened like a regular stake would
+     * @returns amount of tokens that got restored
+     */
+    fn restore_stake(
+        &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        round: u16,
+        outcome: Outcome,
+        amount: Balance,
+    ) -> Balance {
+        let mut window = self.get_resolution_window(round);
+        if window.bonded_outcome.is_some() {
+            return 0;
+        }
+
+        let stake_on_outcome = window.outcome_to_stake.get(&outcome).unwrap_or(0);
+        let restorable = std::cmp::min(amount, window.bond_size - stake_on_outcome);
+        if restorable == 0 {
+            return 0;
+        }
+
+        match self {
+            // only the latest round of an active request can be unbonded
+            DataRequest::Active(dr) => {
+                dr.stake(contract, sender, outcome, restorable);
+            }
+            DataRequest::Finalized(dr) => {
+                window.restore_stake(contract, sender, outcome, restorable);
+                dr.resolution_windows.replace(round as u64, &window);
+            }
+        }
+
+        restorable
+    }
+
+    fn get_resolution_window(&self, round: u16) -> ResolutionWindow {
+        let resolution_windows = match self {
+            DataRequest::Active(dr) => &dr.resolution_windows,
+            DataRequest::Finalized(dr) => &dr.resolution_windows,
+        };
+
+        resolution_windows
+            .get(round as u64)
+            .expect("ERR_NO_RESOLUTION_WINDOW")
     }
 
     fn get_config_id(&self) -> u64 {
@@ -93,7 +199,19 @@ trait ActiveDataRequestChange {
         paid_fee: Balance,
         request_data: NewDataRequestArgs,
     ) -> Self;
-    fn stake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance) -> Balance;
+    fn stake(
+        &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        outcome: Outcome,
+        amount: Balance,
+    ) -> Balance;
+    fn bond_by_providers(
+        &mut self,
+        contract: &mut Contract,
+        outcome: Outcome,
+        challenge_duration: u64,
+    );
     fn invoke_final_arbitrator(&mut self, bond_size: Balance) -> bool;
     fn get_final_outcome(&self) -> Outcome;
 }
@@ -135,12 +253,19 @@ impl ActiveDataRequestChange for ActiveDataRequest {
     }
 
     // @returns amount of tokens that didn't get staked
-    fn stake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance) -> Balance {
+    fn stake(
+        &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        outcome: Outcome,
+        amount: Balance,
+    ) -> Balance {
         let mut window: ResolutionWindow = match self.resolution_windows.len() {
             0 => ResolutionWindowHandler::new(
                 self.id,
                 0,
-                self.calc_resolution_bond(),
+                self.calc_resolution_bond(contract),
+                contract.bond_growth(self.id),
                 self.initial_challenge_period,
                 env::block_timestamp(),
             ),
@@ -150,7 +275,7 @@ impl ActiveDataRequestChange for ActiveDataRequest {
                 .unwrap(),
         };
 
-        let unspent = window.stake(sender, outcome, amount);
+        let unspent = window.stake(contract, sender, outcome, amount);
 
         // If first window push it to vec, else replace updated window struct
         if self.resolution_windows.len() == 0 {
@@ -166,16 +291,22 @@ impl ActiveDataRequestChange for ActiveDataRequest {
         // If the final arbitrator is invoked other stake won't come through.
         if window.bonded_outcome.is_some() && !self.invoke_final_arbitrator(window.bond_size) {
             
+            let settings = contract.bond_settings.get(&self.id);
             // First challenge window should have initial_challenge_period 
             let duration = match self.resolution_windows.len() {
                 1 => self.initial_challenge_period,
-                _ => self.request_config.default_challenge_window_duration,
+                _ => settings
+                    .as_ref()
+                    .and_then(|settings| settings.challenge_duration)
+                    .map(u64::from)
+                    .unwrap_or(self.request_config.default_challenge_window_duration),
             };
 
             self.resolution_windows.push(&ResolutionWindowHandler::new(
                 self.id,
                 self.resolution_windows.len() as u16,
                 window.bond_size,
+                contract.bond_growth(self.id),
                 duration,
                 env::block_timestamp(),
             ));
@@ -184,6 +315,34 @@ impl ActiveDataRequestChange for ActiveDataRequest {
         unspent
     }
 
+    /**
+     * @notice Bonds the first round on the outcome the providers agreed on without any stake, and opens the next
+     * round for `challenge_duration` in which stakers can dispute it. That round's bond is the regular first round
+     * bond so disputing the providers costs the same as bonding the first round would have
+     */
+    fn bond_by_providers(
+        &mut self,
+        contract: &mut Contract,
+        outcome: Outcome,
+        challenge_duration: u64,
+    ) {
+        let now = env::block_timestamp();
+        let mut window = ResolutionWindowHandler::new(self.id, 0, 0, BOND_GROWTH_DIVISOR, 0, now);
+        window.bonded_outcome = Some(outcome.clone());
+        logger::log_resolution_window(&window);
+        contract.add_bonded(self.id, 0, &outcome, 0);
+        self.resolution_windows.push(&window);
+
+        self.resolution_windows.push(&ResolutionWindowHandler::new(
+            self.id,
+            1,
+            self.calc_resolution_bond(contract),
+            BOND_GROWTH_DIVISOR,
+            challenge_duration,
+            now,
+        ));
+    }
+
     // @returns whether final arbitrator was triggered
     fn invoke_final_arbitrator(&mut self, bond_size: Balance) -> bool {
         let should_invoke = bond_size >= self.request_config.final_arbitrator_invoke_amount;
@@ -207,7 +366,25 @@ impl ActiveDataRequestChange for ActiveDataRequest {
 }
 
 trait FinalizedDataRequestMethods {
-    fn claim(&mut self, account_id: String) -> ClaimRes;
+    fn claim(&mut self, contract: &mut Contract, account_id: String) -> ClaimRes;
+    fn claimable(&self, contract: &Contract, account_id: &AccountId) -> ClaimRes;
+    fn claim_from_windows(
+        &mut self,
+        contract: &Contract,
+        account_id: &AccountId,
+    ) -> (Balance, Balance, Balance);
+    fn claimable_from_windows(
+        &self,
+        contract: &Contract,
+        account_id: &AccountId,
+    ) -> (Balance, Balance, Balance);
+    fn summarize_claimable(&self, contract: &Contract, account_id: &AccountId) -> ClaimableSummary;
+    fn calc_profits(
+        &self,
+        total_correct_staked: Balance,
+        total_incorrect_staked: Balance,
+        user_correct_stake: Balance,
+    ) -> (Balance, Balance);
     fn summarize_dr(&self) -> FinalizedDataRequestSummary;
     fn finalize(&mut self, final_outcome: Outcome);
     fn return_validity_bond(
@@ -265,21 +442,93 @@ impl FinalizedDataRequestMethods for FinalizedDataRequest {
         }
     }
 
-    fn claim(&mut self, account_id: String) -> ClaimRes {
+    fn claim(&mut self, contract: &mut Contract, account_id: String) -> ClaimRes {
         // Metrics for calculating payout
+        let (total_correct_staked, total_incorrect_staked, user_correct_stake) =
+            if contract.is_stake_tracked(self.id) {
+                let (total_correct_staked, total_incorrect_staked) =
+                    contract.bonded_totals(self.id, &self.finalized_outcome);
+                let user_correct_stake =
+                    contract.claim_user_stake(self.id, &account_id, &self.finalized_outcome);
+                (
+                    total_correct_staked,
+                    total_incorrect_staked,
+                    user_correct_stake,
+                )
+            } else {
+                self.claim_from_windows(contract, &account_id)
+            };
+        contract.remove_claimed_stakes(&account_id, self.id);
+
+        let (stake_profit, fee_profit) = self.calc_profits(
+            total_correct_staked,
+            total_incorrect_staked,
+            user_correct_stake,
+        );
+
+        logger::log_claim(
+            &account_id,
+            self.id,
+            total_correct_staked,
+            total_incorrect_staked,
+            user_correct_stake,
+            stake_profit,
+            fee_profit,
+        );
+
+        ClaimRes {
+            payment_token_payout: fee_profit,
+            stake_token_payout: user_correct_stake + stake_profit,
+        }
+    }
+
+    // @returns what `claim` would pay out to `account_id` without changing state
+    fn claimable(&self, contract: &Contract, account_id: &AccountId) -> ClaimRes {
+        let (total_correct_staked, total_incorrect_staked, user_correct_stake) =
+            if contract.is_stake_tracked(self.id) {
+                let (total_correct_staked, total_incorrect_staked) =
+                    contract.bonded_totals(self.id, &self.finalized_outcome);
+                let user_correct_stake =
+                    contract.user_correct_stake(self.id, account_id, &self.finalized_outcome);
+                (
+                    total_correct_staked,
+                    total_incorrect_staked,
+                    user_correct_stake,
+                )
+            } else {
+                self.claimable_from_windows(contract, account_id)
+            };
+
+        let (stake_profit, fee_profit) = self.calc_profits(
+            total_correct_staked,
+            total_incorrect_staked,
+            user_correct_stake,
+        );
+
+        ClaimRes {
+            payment_token_payout: fee_profit,
+            stake_token_payout: user_correct_stake + stake_profit,
+        }
+    }
+
+    /**
+     * @notice Claims by iterating over all windows, used for data requests created before stake totals were kept.
+     * Their totals are not backfilled on upgrade, so claims on them keep costing gas linear in their number of rounds
+     * @returns the total correctly and incorrectly bonded stake and the correct stake of `account_id`
+     */
+    fn claim_from_windows(
+        &mut self,
+        contract: &Contract,
+        account_id: &AccountId,
+    ) -> (Balance, Balance, Balance) {
         let mut total_correct_staked = 0;
         let mut total_incorrect_staked = 0;
         let mut user_correct_stake = 0;
 
-        // For any round after the resolution round handle generically
-        // AUDIT: This may run out gas, if the number of windows is too large, because you iterate
-        //     through all windows.
-        // SOLUTION: See if more expensive to iterate through resolution windows than it is to
-        // store aggregate of amount of stake for each user alongside resolution windows and amount they have staked in
         for round in 0..self.resolution_windows.len() {
             let mut window = self.resolution_windows.get(round).unwrap();
             let stake_state: WindowStakeResult =
-                window.claim_for(account_id.to_string(), &self.finalized_outcome);
+                window.claim_for(contract, account_id.to_string(), &self.finalized_outcome);
             match stake_state {
                 WindowStakeResult::Correct(correctly_staked) => {
                     total_correct_staked += correctly_staked.bonded_stake;
@@ -294,6 +543,59 @@ impl FinalizedDataRequestMethods for FinalizedDataRequest {
             self.resolution_windows.replace(round as u64, &window);
         }
 
+        (
+            total_correct_staked,
+            total_incorrect_staked,
+            user_correct_stake,
+        )
+    }
+
+    // @returns what `claim_from_windows` would return without changing state
+    fn claimable_from_windows(
+        &self,
+        contract: &Contract,
+        account_id: &AccountId,
+    ) -> (Balance, Balance, Balance) {
+        let mut total_correct_staked = 0;
+        let mut total_incorrect_staked = 0;
+        let mut user_correct_stake = 0;
+
+        for window in self.resolution_windows.iter() {
+            match window.stake_result_for(contract, account_id, &self.finalized_outcome) {
+                WindowStakeResult::Correct(correctly_staked) => {
+                    total_correct_staked += correctly_staked.bonded_stake;
+                    user_correct_stake += correctly_staked.user_stake;
+                }
+                WindowStakeResult::Incorrect(incorrectly_staked) => {
+                    total_incorrect_staked += incorrectly_staked
+                }
+                WindowStakeResult::NoResult => (),
+            }
+        }
+
+        (
+            total_correct_staked,
+            total_incorrect_staked,
+            user_correct_stake,
+        )
+    }
+
+    fn summarize_claimable(&self, contract: &Contract, account_id: &AccountId) -> ClaimableSummary {
+        let claimable = self.claimable(contract, account_id);
+        ClaimableSummary {
+            request_id: U64(self.id),
+            payment_token_payout: U128(claimable.payment_token_payout),
+            stake_token_payout: U128(claimable.stake_token_payout),
+        }
+    }
+
+    // @returns the user's share of the incorrectly staked tokens and of the paid fee
+    fn calc_profits(
+        &self,
+        total_correct_staked: Balance,
+        total_incorrect_staked: Balance,
+        user_correct_stake: Balance,
+    ) -> (Balance, Balance) {
         let stake_profit = match total_correct_staked {
             0 => 0,
             _ => helpers::calc_product(
@@ -308,37 +610,29 @@ impl FinalizedDataRequestMethods for FinalizedDataRequest {
             _ => helpers::calc_product(user_correct_stake, self.paid_fee, total_correct_staked),
         };
 
-        logger::log_claim(
-            &account_id,
-            self.id,
-            total_correct_staked,
-            total_incorrect_staked,
-            user_correct_stake,
-            stake_profit,
-            fee_profit,
-        );
-
-        ClaimRes {
-            payment_token_payout: fee_profit,
-            stake_token_payout: user_correct_stake + stake_profit,
-        }
+        (stake_profit, fee_profit)
     }
 }
 
 trait ActiveDataRequestView {
-    fn assert_valid_outcome(&self, outcome: &Outcome);
+    fn assert_valid_outcome(&self, contract: &Contract, outcome: &Outcome);
     fn assert_valid_outcome_type(&self, outcome: &Outcome);
     fn assert_can_stake_on_outcome(&self, outcome: &Outcome);
     fn assert_can_finalize(&self);
     fn assert_final_arbitrator(&self);
     fn assert_final_arbitrator_invoked(&self);
     fn assert_final_arbitrator_not_invoked(&self);
-    fn calc_resolution_bond(&self) -> Balance;
+    fn calc_resolution_bond(&self, contract: &Contract) -> Balance;
+    fn calc_first_round_bond(&self, contract: &Contract) -> Balance;
     fn summarize_dr(&self) -> ActiveDataRequestSummary;
 }
 
 impl ActiveDataRequestView for ActiveDataRequest {
-    fn assert_valid_outcome(&self, outcome: &Outcome) {
+    fn assert_valid_outcome(&self, contract: &Contract, outcome: &Outcome) {
+        contract.assert_within_bounds(self.id, outcome);
+        if contract.assert_valid_answer_format(self.id, outcome, self.outcomes.as_ref()) {
+            return;
+        }
         match &self.outcomes {
             Some(outcomes) => match outcome {
                 Outcome::Answer(outcome) => {
@@ -436,12 +730,23 @@ impl ActiveDataRequestView for ActiveDataRequest {
 
     /**
      * @notice Calculates the size of the resolution bond. If the accumulated fee is smaller than the validity bond, we payout the validity bond to validators, thus they have to stake double in order to be
-     * eligible for the reward, in the case that the fee is greater than the validity bond validators need to have a cumulative stake of double the fee amount
+     * eligible for the reward, in the case that the fee is greater than the validity bond validators need to have a cumulative stake of double the fee amount.
+     * Fee top-ups are left out so that raising the reward doesn't also raise the bond stakers have to put up, an initial
+     * bond set by the requester replaces this rule
      * @returns The size of the initial `resolution_bond` denominated in `stake_token`
      */
-    fn calc_resolution_bond(&self) -> Balance {
-        let base_bond = if self.request_config.paid_fee >= self.request_config.validity_bond {
-            self.request_config.paid_fee
+    fn calc_resolution_bond(&self, contract: &Contract) -> Balance {
+        let initial_bond = contract
+            .bond_settings
+            .get(&self.id)
+            .and_then(|settings| settings.initial_bond);
+        if let Some(initial_bond) = initial_bond {
+            return multiply_stake(initial_bond.into(), self.request_config.stake_multiplier);
+        }
+
+        let paid_fee = self.request_config.paid_fee - contract.fee_top_up_total(self.id);
+        let base_bond = if paid_fee >= self.request_config.validity_bond {
+            paid_fee
         } else {
             self.request_config.validity_bond
         };
@@ -455,6 +760,19 @@ impl ActiveDataRequestView for ActiveDataRequest {
         multiply_stake(res_bond, self.request_config.stake_multiplier)
     }
 
+    /**
+     * @notice Calculates the bond of the first resolution window, which grows the resolution bond by the request's
+     * bond growth the same way every later round does
+     * @returns The amount of `stake_token` needed to bond round 0
+     */
+    fn calc_first_round_bond(&self, contract: &Contract) -> Balance {
+        helpers::calc_product(
+            self.calc_resolution_bond(contract),
+            contract.bond_growth(self.id).into(),
+            BOND_GROWTH_DIVISOR.into(),
+        )
+    }
+
     /**
      * @notice Transforms a data request struct into another struct with Serde serialization
      */
@@ -508,6 +826,23 @@ impl Contract {
         sender: AccountId,
         amount: Balance,
         payload: NewDataRequestArgs,
+    ) -> Balance {
+        self.dr_new_with_settings(sender, amount, payload, DataRequestSettings::default())
+    }
+
+    /**
+     * @notice Creates a data request with `settings` on top of its `NewDataRequestArgs`. Bond settings have to be
+     * within the `bond_limits` set by governance, numeric bounds can only be set on number requests and answer
+     * formats only on string requests
+     * @returns amount of tokens that didn't get used
+     */
+    #[payable]
+    pub fn dr_new_with_settings(
+        &mut self,
+        sender: AccountId,
+        amount: Balance,
+        payload: NewDataRequestArgs,
+        settings: DataRequestSettings,
     ) -> Balance {
         self.assert_unpaused();
         let config = self.get_config();
@@ -521,23 +856,20 @@ impl Contract {
             amount
         );
         self.dr_validate(&payload);
+        self.dr_validate_settings(&payload, &settings);
 
-        let paid_fee = amount - validity_bond;
-
-        let requester = self.whitelist.whitelist_get_expect(&sender);
-        let dr = ActiveDataRequest::new(
-            requester,
-            self.data_requests.len() as u64, // dr_id
-            self.configs.len() - 1,          // dr's config id
-            &config,
-            paid_fee,
-            payload,
+        let resolution_fee = fee_config::calc_resolution_fee(&config);
+        assert!(
+            amount - validity_bond >= resolution_fee,
+            "Resolution fee of {} not reached, received only {}",
+            resolution_fee,
+            amount - validity_bond
         );
 
-        logger::log_new_data_request(&dr);
-        self.data_requests.push(&DataRequest::Active(dr));
+        self.dr_create(&sender, &config, resolution_fee, payload, settings);
 
-        0
+        // refund anything paid on top of the validity bond and resolution fee
+        amount - validity_bond - resolution_fee
     }
 
     #[payable]
@@ -551,12 +883,27 @@ impl Contract {
         let mut dr = self.dr_get_expect_active(payload.id.into());
         let config = self.configs.get(dr.global_config_id).unwrap();
         self.assert_sender(&config.stake_token);
+        self.assert_commits_settled(dr.id);
+        self.assert_provider_quorum_closed(dr.id);
+        assert!(
+            dr.provider.is_none() || dr.resolution_windows.len() > 0,
+            "Error provider requests can only be staked on once the provider proposed an outcome"
+        );
         dr.assert_final_arbitrator_not_invoked();
-        dr.assert_can_stake_on_outcome(&payload.outcome);
-        dr.assert_valid_outcome(&payload.outcome);
-        dr.assert_valid_outcome_type(&payload.outcome);
+        dr.assert_valid_outcome(self, &payload.outcome);
+        let round = dr.resolution_windows.len().saturating_sub(1) as u16;
+        let outcome = self.stake_outcome(
+            dr.id,
+            round,
+            payload.outcome,
+            dr.resolution_windows
+                .iter()
+                .filter_map(|window| window.bonded_outcome),
+        );
+        dr.assert_can_stake_on_outcome(&outcome);
+        dr.assert_valid_outcome_type(&outcome);
 
-        let unspent_stake = dr.stake(sender, payload.outcome, amount);
+        let unspent_stake = dr.stake(self, sender, outcome, amount);
         logger::log_update_active_data_request(&dr);
         self.data_requests
             .replace(payload.id.into(), &DataRequest::Active(dr));
@@ -564,83 +911,528 @@ impl Contract {
         PromiseOrValue::Value(U128(unspent_stake))
     }
 
-    #[payable]
-    pub fn dr_unstake(
+    /**
+     * @notice Proposes the outcome of a provider data request by bonding its first round with the provider's stake.
+     * Stakers can challenge the proposal during the request's challenge period, after which it resolves through
+     * regular bonded rounds in which a wrong provider loses its bond to the challengers
+     * @returns amount of tokens that didn't get bonded
+     */
+    pub fn dr_propose_by_provider(
         &mut self,
-        request_id: U64,
-        resolution_round: u16,
-        outcome: Outcome,
-        amount: U128,
-    ) {
+        sender: AccountId,
+        amount: Balance,
+        payload: StakeDataRequestArgs,
+    ) -> PromiseOrValue<WrappedBalance> {
         self.assert_unpaused();
-        let initial_storage = env::storage_usage();
-
-        let mut dr = self.dr_get_expect(request_id.into());
-        let unstaked = dr.unstake(
-            env::predecessor_account_id(),
-            resolution_round,
-            outcome,
-            amount.into(),
+        let mut dr = self.dr_get_expect_active(payload.id.into());
+        let config = self.configs.get(dr.global_config_id).unwrap();
+        self.assert_sender(&config.stake_token);
+        match &dr.provider {
+            Some(provider) => assert_eq!(
+                provider, &sender,
+                "this request can only be proposed by the provider"
+            ),
+            None => panic!("error this is not a provider data request"),
+        };
+        assert!(
+            dr.resolution_windows.len() == 0,
+            "Error provider already proposed an outcome"
         );
-        let config = self.configs.get(dr.get_config_id()).unwrap();
+        let bond = dr.calc_first_round_bond(self);
+        assert!(
+            amount >= bond,
+            "Provider bond of {} not reached, received only {}",
+            bond,
+            amount
+        );
+        dr.assert_valid_outcome(self, &payload.outcome);
+        dr.assert_valid_outcome_type(&payload.outcome);
 
-        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+        let unspent_stake = dr.stake(self, sender, payload.outcome, amount);
+        logger::log_update_active_data_request(&dr);
+        self.data_requests
+            .replace(payload.id.into(), &DataRequest::Active(dr));
 
-        dr.log_update();
-        fungible_token_transfer(config.stake_token, env::predecessor_account_id(), unstaked);
+        PromiseOrValue::Value(U128(unspent_stake))
     }
 
     /**
-     * @returns amount of tokens claimed
+     * @notice Adds `amount` of payment tokens to the fee of an active data request, which is paid out to its correct stakers
+     * @returns amount of tokens that weren't added
      */
-
-    #[payable]
-    pub fn dr_claim(&mut self, account_id: String, request_id: U64) -> Promise {
+    pub fn dr_top_up(
+        &mut self,
+        sender: AccountId,
+        amount: Balance,
+        payload: TopUpDataRequestArgs,
+    ) -> PromiseOrValue<WrappedBalance> {
         self.assert_unpaused();
-        let initial_storage = env::storage_usage();
-
-        let mut dr = self.dr_get_expect_finalized(request_id.into());
-        let stake_payout = dr.claim(account_id.to_string());
+        let mut dr = self.dr_get_expect_active(payload.id);
         let config = self.configs.get(dr.global_config_id).unwrap();
+        self.assert_sender(&config.payment_token);
+        assert!(amount > 0, "top-up has to be higher than 0");
 
-        logger::log_update_finalized_data_request(&dr);
-        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+        dr.request_config.paid_fee += amount;
+        self.add_fee_top_up(dr.id, &sender, amount);
+        logger::log_fee_top_up(&dr, &sender, amount);
+        self.data_requests
+            .replace(payload.id.into(), &DataRequest::Active(dr));
+
+        PromiseOrValue::Value(U128(0))
+    }
 
+    /**
+     * @notice Commits `amount` of stake tokens to the first round of a data request staked through commits
+     * @returns amount of tokens that didn't get committed
+     */
+    pub fn dr_commit_stake(
+        &mut self,
+        sender: AccountId,
+        amount: Balance,
+        payload: CommitStakeArgs,
+    ) -> PromiseOrValue<WrappedBalance> {
+        self.assert_unpaused();
+        let dr = self.dr_get_expect_active(payload.id);
+        let config = self.configs.get(dr.global_config_id).unwrap();
+        self.assert_sender(&config.stake_token);
+        let min_commit = helpers::calc_product(
+            dr.calc_first_round_bond(self),
+            self.extended_config.min_commit_share.into(),
+            commit_reveal::MIN_COMMIT_SHARE_DIVISOR.into(),
+        );
         assert!(
-            env::prepaid_gas() - env::used_gas() >= GAS_BASE_TRANSFER,
-            "not enough gas for both token transfers"
+            amount > 0 && amount >= min_commit,
+            "Minimum commit of {} not reached, received only {}",
+            min_commit,
+            amount
+        );
+        assert!(
+            payload_reveal::is_digest(&payload.commitment),
+            "commitment has to be a sha256 digest"
         );
 
-        // transfer owed stake tokens
-        let prev_prom = if stake_payout.stake_token_payout > 0 {
-            Some(fungible_token_transfer(
-                config.stake_token,
-                account_id.to_string(),
-                stake_payout.stake_token_payout,
-            ))
-        } else {
+        let mut round = self
+            .commit_rounds
+            .get(&dr.id)
+            .expect("Error DataRequest is not staked through commits");
+        assert!(
+            env::block_timestamp() < u64::from(round.commit_end),
+            "Error commit phase has ended"
+        );
+        assert!(
+            round.commits.len() < commit_reveal::MAX_COMMITS,
+            "Error DataRequest has reached the maximum number of commits"
+        );
+        assert!(
+            round.commits.iter().all(|commit| commit.account_id != sender),
+            "Error {} already committed on this DataRequest",
+            sender
+        );
+
+        round.commits.push(commit_reveal::StakeCommit {
+            account_id: sender,
+            commitment: payload.commitment,
+            amount: U128(amount),
+            revealed: None,
+        });
+        self.commit_rounds.insert(&dr.id, &round);
+        logger::log_commit_round(dr.id, &round);
+
+        PromiseOrValue::Value(U128(0))
+    }
+
+    /**
+     * @notice Reveals the outcome the caller committed stake on, the commitment has to match `outcome` and `salt`
+     */
+    pub fn dr_reveal_stake(&mut self, request_id: U64, outcome: Outcome, salt: String) {
+        self.assert_unpaused();
+        let sender = env::predecessor_account_id();
+        let dr = self.dr_get_expect_active(request_id);
+        let mut round = self
+            .commit_rounds
+            .get(&dr.id)
+            .expect("Error DataRequest is not staked through commits");
+        let now = env::block_timestamp();
+        assert!(
+            now >= u64::from(round.commit_end) && now < u64::from(round.reveal_end),
+            "Error DataRequest is not in its reveal phase"
+        );
+        dr.assert_valid_outcome(self, &outcome);
+        dr.assert_valid_outcome_type(&outcome);
+
+        let commit = round
+            .commits
+            .iter_mut()
+            .find(|commit| commit.account_id == sender)
+            .expect("Error no commit found for this account");
+        assert!(
+            commit.revealed.is_none(),
+            "Error commit has already been revealed"
+        );
+        assert_eq!(
+            commit.commitment,
+            commit_reveal::commitment(dr.id, &sender, &outcome, &salt),
+            "Error outcome and salt don't match the commitment"
+        );
+
+        // revealed answers within tolerance of each other are settled as the same outcome
+        commit.revealed = Some(self.stake_outcome(dr.id, 0, outcome, std::iter::empty()));
+        self.commit_rounds.insert(&dr.id, &round);
+        logger::log_commit_round(dr.id, &round);
+    }
+
+    /**
+     * @notice Applies the revealed commits of a data request to its first resolution window once the reveal phase has
+     * ended. Stake on the outcome with the most revealed stake is applied first so that it gets bonded if it fills the
+     * bond, stake that doesn't fit in the window is refunded. Unrevealed commits are slashed or refunded depending on
+     * `slash_unrevealed_commits`. Callable by anyone
+     */
+    pub fn dr_settle_commits(&mut self, request_id: U64) {
+        self.assert_unpaused();
+        let mut dr = self.dr_get_expect_active(request_id);
+        let mut round = self
+            .commit_rounds
+            .get(&dr.id)
+            .expect("Error DataRequest is not staked through commits");
+        assert!(!round.settled, "Error commits have already been settled");
+        assert!(
+            env::block_timestamp() >= u64::from(round.reveal_end),
+            "Error reveal phase has not ended yet"
+        );
+        let config = self.configs.get(dr.global_config_id).unwrap();
+
+        for i in round.settlement_order() {
+            let commit = &round.commits[i];
+            let amount: u128 = commit.amount.into();
+            let window_bonded = dr
+                .resolution_windows
+                .get(0)
+                .map(|window| window.bonded_outcome.is_some())
+                .unwrap_or(false);
+            let unspent = if window_bonded {
+                amount
+            } else {
+                dr.stake(
+                    self,
+                    commit.account_id.to_string(),
+                    commit.revealed.clone().unwrap(),
+                    amount,
+                )
+            };
+            if unspent > 0 {
+                self.payout(
+                    config.stake_token.to_string(),
+                    commit.account_id.to_string(),
+                    unspent,
+                );
+            }
+        }
+
+        for commit in round.commits.iter().filter(|commit| commit.revealed.is_none()) {
+            let receiver = if self.extended_config.slash_unrevealed_commits {
+                config.gov.to_string()
+            } else {
+                commit.account_id.to_string()
+            };
+            self.payout(config.stake_token.to_string(), receiver, commit.amount.into());
+        }
+
+        round.settled = true;
+        self.commit_rounds.insert(&dr.id, &round);
+        logger::log_commit_round(dr.id, &round);
+        logger::log_update_active_data_request(&dr);
+        self.data_requests
+            .replace(request_id.into(), &DataRequest::Active(dr));
+    }
+
+    /**
+     * @notice Submits the outcome of one of the providers of a data request with a provider quorum. Once `threshold`
+     * providers submitted the same outcome the first round is bonded on it and a challenge window opens
+     */
+    pub fn dr_submit_provider_outcome(&mut self, request_id: U64, outcome: Outcome) {
+        self.assert_unpaused();
+        let sender = env::predecessor_account_id();
+        let mut dr = self.dr_get_expect_active(request_id);
+        let mut quorum = self
+            .provider_quorums
+            .get(&dr.id)
+            .expect("Error DataRequest has no provider quorum");
+        assert!(
+            quorum.agreed_outcome.is_none(),
+            "Error providers already agreed on an outcome"
+        );
+        assert!(
+            env::block_timestamp() < u64::from(quorum.submission_end),
+            "Error provider submission phase has ended"
+        );
+        assert!(
+            quorum.providers.contains(&sender),
+            "Error {} is not a provider of this DataRequest",
+            sender
+        );
+        assert!(
+            quorum
+                .submissions
+                .iter()
+                .all(|submission| submission.account_id != sender),
+            "Error {} already submitted an outcome",
+            sender
+        );
+        dr.assert_valid_outcome(self, &outcome);
+        dr.assert_valid_outcome_type(&outcome);
+
+        quorum.submissions.push(ProviderSubmission {
+            account_id: sender,
+            outcome,
+        });
+        if let Some(agreed_outcome) = quorum.find_agreement() {
+            dr.bond_by_providers(
+                self,
+                agreed_outcome.clone(),
+                quorum.challenge_duration.into(),
+            );
+            quorum.agreed_outcome = Some(agreed_outcome);
+            logger::log_update_active_data_request(&dr);
+            self.data_requests
+                .replace(request_id.into(), &DataRequest::Active(dr));
+        }
+        self.provider_quorums.insert(&request_id.into(), &quorum);
+        logger::log_provider_quorum(request_id.into(), &quorum);
+    }
+
+    #[payable]
+    pub fn dr_unstake(
+        &mut self,
+        request_id: U64,
+        resolution_round: u16,
+        outcome: Outcome,
+        amount: U128,
+    ) -> Promise {
+        self.assert_unpaused();
+        let initial_storage = env::storage_usage();
+        let sender = env::predecessor_account_id();
+
+        let mut dr = self.dr_get_expect(request_id);
+        let unstaked = dr.unstake(
+            self,
+            sender.to_string(),
+            resolution_round,
+            outcome.clone(),
+            amount.into(),
+        );
+        let config = self.configs.get(dr.get_config_id()).unwrap();
+        self.data_requests.replace(request_id.into(), &dr);
+
+        helpers::refund_storage(initial_storage, sender.to_string());
+
+        assert!(
+            env::prepaid_gas() - env::used_gas() >= GAS_BASE_TRANSFER + GAS_BASE_RESOLVE_TRANSFER,
+            "not enough gas for the token transfer and its callback"
+        );
+
+        dr.log_update();
+        fungible_token_transfer(config.stake_token, sender.to_string(), unstaked).then(
+            ext_self::dr_unstake_callback(
+                request_id,
+                resolution_round,
+                sender,
+                outcome,
+                U128(unstaked),
+                // NEAR params
+                &env::current_account_id(),
+                0,
+                GAS_BASE_RESOLVE_TRANSFER,
+            ),
+        )
+    }
+
+    /**
+     * @notice restores the stake of `account_id` if the `ft_transfer` of an unstake failed, the part that can't be
+     * restored is recorded as a failed payout
+     * @returns whether the unstaked tokens were transferred
+     */
+    #[private]
+    pub fn dr_unstake_callback(
+        &mut self,
+        request_id: U64,
+        resolution_round: u16,
+        account_id: AccountId,
+        outcome: Outcome,
+        amount: U128,
+    ) -> bool {
+        if helpers::is_promise_success() {
+            return true;
+        }
+
+        let amount: Balance = amount.into();
+        let mut dr = self.dr_get_expect(request_id);
+        let restored = dr.restore_stake(
+            self,
+            account_id.to_string(),
+            resolution_round,
+            outcome,
+            amount,
+        );
+        self.data_requests.replace(request_id.into(), &dr);
+        dr.log_update();
+
+        // stake that doesn't fit in the window anymore is owed to the account instead
+        if restored < amount {
+            let config = self.configs.get(dr.get_config_id()).unwrap();
+            self.record_failed_payout(&account_id, config.stake_token, amount - restored);
+        }
+
+        false
+    }
+
+    /**
+     * @returns amount of tokens claimed
+     */
+
+    #[payable]
+    pub fn dr_claim(&mut self, account_id: String, request_id: U64) -> Promise {
+        self.assert_unpaused();
+        let initial_storage = env::storage_usage();
+
+        let mut dr = self.dr_get_expect_finalized(request_id.into());
+        let stake_payout = dr.claim(self, account_id.to_string());
+        let config = self.configs.get(dr.global_config_id).unwrap();
+
+        logger::log_update_finalized_data_request(&dr);
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+
+        assert!(
+            env::prepaid_gas() - env::used_gas()
+                >= 2 * (GAS_BASE_TRANSFER + GAS_BASE_RESOLVE_TRANSFER),
+            "not enough gas for both token transfers"
+        );
+
+        self.payout_claim(
+            config.stake_token,
+            config.payment_token,
+            account_id,
+            stake_payout,
+        )
+        .expect("can't claim 0")
+    }
+
+    /**
+     * @notice Claims for `account_id` on multiple data requests and pays out the sum in at most one transfer per token
+     * @dev Data requests that are not finalized are skipped, data requests configured with other tokens than the first
+     * claimed data request are left for a next call
+     * @returns ids of the data requests that were not claimed because they use other tokens or because gas ran out,
+     * resolved after the payout transfers if anything is paid out
+     */
+    #[payable]
+    pub fn dr_claim_many(
+        &mut self,
+        account_id: String,
+        request_ids: Vec<U64>,
+    ) -> PromiseOrValue<Vec<U64>> {
+        self.assert_unpaused();
+        let initial_storage = env::storage_usage();
+        // both payout transfers and the callback passing on the unclaimed ids
+        let transfer_gas =
+            2 * (GAS_BASE_TRANSFER + GAS_BASE_RESOLVE_TRANSFER) + GAS_BASE_RESOLVE_TRANSFER;
+
+        let mut tokens: Option<(AccountId, AccountId)> = None;
+        let mut total_payout = ClaimRes {
+            payment_token_payout: 0,
+            stake_token_payout: 0,
+        };
+        let mut unclaimed = Vec::new();
+        // claims with many resolution windows use more gas, so keep room for the most expensive claim so far
+        let mut claim_gas = GAS_BASE_CLAIM;
+
+        for (i, request_id) in request_ids.iter().enumerate() {
+            if env::prepaid_gas() - env::used_gas() < transfer_gas + claim_gas {
+                unclaimed.extend_from_slice(&request_ids[i..]);
+                break;
+            }
+
+            let gas_before = env::used_gas();
+            let mut dr = match self.data_requests.get(request_id.0) {
+                Some(DataRequest::Finalized(dr)) => dr,
+                _ => continue,
+            };
+
+            let config = self.configs.get(dr.global_config_id).unwrap();
+            let dr_tokens = (config.stake_token, config.payment_token);
+            match &tokens {
+                Some(claim_tokens) if claim_tokens != &dr_tokens => {
+                    unclaimed.push(*request_id);
+                    continue;
+                }
+                Some(_) => (),
+                None => tokens = Some(dr_tokens),
+            }
+
+            let stake_payout = dr.claim(self, account_id.to_string());
+            logger::log_update_finalized_data_request(&dr);
+            total_payout.stake_token_payout += stake_payout.stake_token_payout;
+            total_payout.payment_token_payout += stake_payout.payment_token_payout;
+
+            claim_gas = std::cmp::max(claim_gas, env::used_gas() - gas_before);
+        }
+
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+
+        let payout = tokens.and_then(|(stake_token, payment_token)| {
+            self.payout_claim(stake_token, payment_token, account_id, total_payout)
+        });
+
+        match payout {
+            Some(payout) => PromiseOrValue::Promise(payout.then(ext_self::dr_resolve_claim_many(
+                unclaimed,
+                // NEAR params
+                &env::current_account_id(),
+                0,
+                GAS_BASE_RESOLVE_TRANSFER,
+            ))),
+            None => PromiseOrValue::Value(unclaimed),
+        }
+    }
+
+    /**
+     * @notice Passes on the ids `dr_claim_many` did not claim once its payout transfers are resolved
+     */
+    #[private]
+    pub fn dr_resolve_claim_many(&self, unclaimed: Vec<U64>) -> Vec<U64> {
+        unclaimed
+    }
+
+    /**
+     * @notice Transfers the owed stake and payment tokens of a claim
+     * @returns the transfer promise or `None` if nothing is owed
+     */
+    fn payout_claim(
+        &self,
+        stake_token: AccountId,
+        payment_token: AccountId,
+        account_id: AccountId,
+        stake_payout: ClaimRes,
+    ) -> Option<Promise> {
+        // transfer owed stake tokens
+        let prev_prom = if stake_payout.stake_token_payout > 0 {
+            Some(self.payout(
+                stake_token,
+                account_id.to_string(),
+                stake_payout.stake_token_payout,
+            ))
+        } else {
             None
         };
 
         if stake_payout.payment_token_payout > 0 {
             // distribute fee + bond
+            let payment_prom =
+                self.payout(payment_token, account_id, stake_payout.payment_token_payout);
             match prev_prom {
-                Some(p) => p.then(fungible_token_transfer(
-                    config.payment_token,
-                    account_id,
-                    stake_payout.payment_token_payout,
-                )),
-                None => fungible_token_transfer(
-                    config.payment_token,
-                    account_id,
-                    stake_payout.payment_token_payout,
-                ),
+                Some(p) => Some(p.and(payment_prom)),
+                None => Some(payment_prom),
             }
         } else {
-            match prev_prom {
-                Some(p) => p,
-                None => panic!("can't claim 0"),
-            }
+            prev_prom
         }
     }
 
@@ -652,14 +1444,38 @@ impl Contract {
         let requester = dr.requester.account_id.clone();
         let validity_bond = dr.request_config.validity_bond;
         dr.assert_can_finalize();
+        // nobody staked on an undisputed provider quorum outcome, so no staker earned the fee
+        let refund = if self.is_unchallenged_quorum(dr.id, dr.resolution_windows.len()) {
+            dr.request_config.paid_fee - self.fee_top_up_total(dr.id)
+        } else {
+            0
+        };
+        // the validity bond transfer, plus the fee refund if there is one
+        let transfer_gas = match refund {
+            0 => GAS_BASE_TRANSFER,
+            _ => GAS_BASE_TRANSFER + GAS_BASE_PAYOUT,
+        };
+        let set_outcome_gas = self.assert_set_outcome_gas(&requester, transfer_gas);
         let final_outcome = dr.get_final_outcome();
 
-        dr.requester
-            .set_outcome(final_outcome.clone(), dr.tags.clone());
+        self.deliver_final_outcome(
+            request_id.into(),
+            dr.requester.clone(),
+            final_outcome.clone(),
+            dr.tags.clone(),
+            set_outcome_gas,
+        );
 
         let config = self.configs.get(dr.global_config_id).unwrap();
 
         let fdr = self.trim_dr(dr, final_outcome);
+        if refund > 0 {
+            self.payout(
+                config.payment_token.to_string(),
+                requester.to_string(),
+                refund,
+            );
+        }
         fdr.return_validity_bond(config.payment_token, requester, validity_bond);
         logger::log_update_finalized_data_request(&fdr);
         helpers::refund_storage(initial_storage, env::predecessor_account_id());
@@ -667,31 +1483,6 @@ impl Contract {
             .replace(request_id.into(), &DataRequest::Finalized(fdr));
     }
 
-    pub fn dr_finalize_by_provider(&mut self, request_id: U64, outcome: Outcome) {
-        self.assert_unpaused();
-        let initial_storage = env::storage_usage();
-        let dr = self.dr_get_expect_active(request_id.into());
-        dr.assert_valid_outcome(&outcome);
-
-        match &dr.provider {
-            Some(provider) => assert_eq!(
-                provider,
-                &env::predecessor_account_id(),
-                "this request can only be finalized by the provider"
-            ),
-            None => panic!("error this is not a provider data request"),
-        };
-
-        dr.requester.set_outcome(outcome.clone(), dr.tags.clone());
-
-        let fdr = self.trim_dr(dr, outcome);
-        logger::log_update_finalized_data_request(&fdr);
-        self.data_requests
-            .replace(request_id.into(), &DataRequest::Finalized(fdr));
-
-        helpers::refund_storage(initial_storage, env::predecessor_account_id());
-    }
-
     pub fn dr_final_arbitrator_finalize(
         &mut self,
         request_id: U64,
@@ -704,11 +1495,26 @@ impl Contract {
         let requester = dr.requester.account_id.clone();
         let validity_bond = dr.request_config.validity_bond;
         dr.assert_final_arbitrator();
-        dr.assert_valid_outcome(&outcome);
+        dr.assert_valid_outcome(self, &outcome);
         dr.assert_final_arbitrator_invoked();
+        // an outcome within tolerance of a bonded outcome finalizes as that outcome, so its stakers get paid out
+        let outcome = self.match_bonded_outcome(
+            dr.id,
+            outcome,
+            dr.resolution_windows
+                .iter()
+                .filter_map(|window| window.bonded_outcome),
+        );
 
         let config = self.configs.get(dr.global_config_id).unwrap();
-        dr.requester.set_outcome(outcome.clone(), dr.tags.clone());
+        let set_outcome_gas = self.assert_set_outcome_gas(&requester, GAS_BASE_TRANSFER);
+        self.deliver_final_outcome(
+            request_id.into(),
+            dr.requester.clone(),
+            outcome.clone(),
+            dr.tags.clone(),
+            set_outcome_gas,
+        );
         let fdr = self.trim_dr(dr, outcome);
 
         logger::log_update_finalized_data_request(&fdr);
@@ -721,6 +1527,130 @@ impl Contract {
         promise
     }
 
+    /**
+     * @notice Cancels a data request nobody has staked on yet and refunds its validity bond and fee to the requester.
+     * Fee others added through top-ups is left for them to withdraw through `dr_withdraw_top_up`.
+     * `DataRequest` has no cancelled variant so the request stays `Active` and is marked in `cancelled_requests`,
+     * which makes any further staking or finalization panic
+     */
+    #[payable]
+    pub fn dr_cancel(&mut self, request_id: U64) {
+        self.assert_unpaused();
+        let initial_storage = env::storage_usage();
+
+        let dr = self.dr_get_expect_active(request_id);
+        let requester = dr.requester.account_id.clone();
+        self.assert_sender(&requester);
+        assert!(
+            dr.resolution_windows.len() == 0 && !self.has_open_commits(dr.id),
+            "Error DataRequest has already been staked on"
+        );
+
+        let config = self.configs.get(dr.global_config_id).unwrap();
+        let refund = dr.request_config.validity_bond + dr.request_config.paid_fee
+            - self.fee_top_up_total(dr.id);
+        self.cancelled_requests.insert(&request_id.into());
+        logger::log_cancel_data_request(&dr, refund);
+
+        if refund > 0 {
+            self.payout(config.payment_token, requester, refund);
+        }
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+    }
+
+    /**
+     * @notice Finalizes a data request nobody staked on before its expiry as Invalid, callable by anyone.
+     * Refunds the validity bond and fee to the requester since no staker earned them, top-ups go back to their senders
+     * through `dr_withdraw_top_up`
+     */
+    #[payable]
+    pub fn dr_finalize_expired(&mut self, request_id: U64) {
+        self.assert_unpaused();
+        let initial_storage = env::storage_usage();
+
+        let dr = self.dr_get_expect_active(request_id);
+        assert!(
+            dr.resolution_windows.len() == 0 && !self.has_open_commits(dr.id),
+            "Error DataRequest has already been staked on"
+        );
+        let expires_at = self
+            .request_expiries
+            .get(&request_id.into())
+            .expect("Error DataRequest has no expiry");
+        assert!(
+            env::block_timestamp() >= expires_at,
+            "Error DataRequest has not expired yet"
+        );
+
+        let requester = dr.requester.account_id.clone();
+        let refund = dr.request_config.validity_bond + dr.request_config.paid_fee
+            - self.fee_top_up_total(dr.id);
+        let config = self.configs.get(dr.global_config_id).unwrap();
+        let set_outcome_gas = self.assert_set_outcome_gas(&requester, GAS_BASE_PAYOUT);
+        self.deliver_outcome(
+            request_id.into(),
+            dr.requester.clone(),
+            Outcome::Invalid,
+            dr.tags.clone(),
+            true,
+            set_outcome_gas,
+        );
+
+        let fdr = self.trim_dr(dr, Outcome::Invalid);
+        logger::log_update_finalized_data_request(&fdr);
+        self.data_requests
+            .replace(request_id.into(), &DataRequest::Finalized(fdr));
+
+        if refund > 0 {
+            self.payout(config.payment_token, requester, refund);
+        }
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+    }
+
+    /**
+     * @notice Refunds the fee the predecessor added to a data request through top-ups once it is clear no staker
+     * earned it, which is when the request got cancelled, finalized as expired or resolved on an undisputed provider
+     * quorum outcome. The requester gets the rest of the fee back on those same paths
+     */
+    #[payable]
+    pub fn dr_withdraw_top_up(&mut self, request_id: U64) -> Promise {
+        self.assert_unpaused();
+        let initial_storage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+
+        let dr = self.dr_get_expect(request_id);
+        let fee_unearned = match &dr {
+            DataRequest::Active(_) => self.cancelled_requests.contains(&request_id.into()),
+            DataRequest::Finalized(dr) => {
+                let window_count = dr.resolution_windows.len();
+                window_count == 0 || self.is_unchallenged_quorum(dr.id, window_count)
+            }
+        };
+        assert!(fee_unearned, "Error DataRequest fee can't be refunded");
+        let amount = self
+            .take_fee_top_up(request_id.into(), &account_id)
+            .expect("No fee top-up to withdraw");
+
+        assert!(
+            env::prepaid_gas() - env::used_gas() >= GAS_BASE_TRANSFER + GAS_BASE_RESOLVE_TRANSFER,
+            "not enough gas for the token transfer and its callback"
+        );
+
+        let config = self.configs.get(dr.get_config_id()).unwrap();
+        logger::log_fee_top_up_refund(request_id.into(), &account_id, amount);
+        helpers::refund_storage(initial_storage, account_id.to_string());
+        self.payout(config.payment_token, account_id, amount)
+    }
+
+    // @returns the time in ns from which the data request can be finalized as expired if nobody staked on it
+    pub fn get_request_expiry(&self, request_id: U64) -> Option<U64> {
+        self.request_expiries.get(&request_id.into()).map(U64)
+    }
+
+    pub fn dr_is_cancelled(&self, request_id: U64) -> bool {
+        self.cancelled_requests.contains(&request_id.into())
+    }
+
     fn dr_get_expect(&self, id: U64) -> DataRequest {
         self.data_requests
             .get(id.into())
@@ -733,7 +1663,13 @@ impl Contract {
             .get(id.into())
             .expect("Error no DataRequest with this id exists")
         {
-            DataRequest::Active(dr) => dr,
+            DataRequest::Active(dr) => {
+                assert!(
+                    !self.cancelled_requests.contains(&id.into()),
+                    "Error DataRequest is cancelled"
+                );
+                dr
+            }
             DataRequest::Finalized(_) => panic!("Error DataRequest is already finalized"),
         }
     }
@@ -764,8 +1700,38 @@ impl Contract {
         self.get_request_by_id(U64(self.data_requests.len() - 1))
     }
 
-    pub fn get_outcome(&self, dr_id: U64) -> Outcome {
-        self.dr_get_expect_finalized(dr_id.into()).finalized_outcome
+    pub fn get_outcome_stake(
+        &self,
+        request_id: U64,
+        resolution_round: u16,
+        outcome: Outcome,
+    ) -> U128 {
+        let window = self
+            .dr_get_expect(request_id)
+            .get_resolution_window(resolution_round);
+        U128(window.outcome_to_stake.get(&outcome).unwrap_or(0))
+    }
+
+    pub fn get_user_outcome_stake(
+        &self,
+        request_id: U64,
+        resolution_round: u16,
+        account_id: AccountId,
+        outcome: Outcome,
+    ) -> U128 {
+        let window = self
+            .dr_get_expect(request_id)
+            .get_resolution_window(resolution_round);
+        U128(
+            window
+                .get_user_to_outcomes(&account_id)
+                .get(&outcome)
+                .unwrap_or(0),
+        )
+    }
+
+    pub fn get_outcome(&self, dr_id: U64) -> Outcome {
+        self.dr_get_expect_finalized(dr_id.into()).finalized_outcome
     }
 
     pub fn get_requests(&self, from_index: U64, limit: U64) -> Vec<DataRequestSummary> {
@@ -774,9 +1740,97 @@ impl Contract {
             .map(|index| self.data_requests.get(index).unwrap().summarize())
             .collect()
     }
+
+    /**
+     * @returns what `dr_claim` would pay out to `account_id`, or `None` if the data request does not exist or is not finalized
+     */
+    pub fn get_claimable(
+        &self,
+        account_id: AccountId,
+        request_id: U64,
+    ) -> Option<ClaimableSummary> {
+        match self.data_requests.get(request_id.into()) {
+            Some(DataRequest::Finalized(dr)) => Some(dr.summarize_claimable(self, &account_id)),
+            _ => None,
+        }
+    }
+
+    /**
+     * @returns the non-zero claimable payouts of `account_id` on the finalized data requests in `from_index..from_index + limit`
+     */
+    pub fn get_claimables(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: U64,
+    ) -> Vec<ClaimableSummary> {
+        let i: u64 = from_index.into();
+        (i..std::cmp::min(i + u64::from(limit), self.data_requests.len()))
+            .filter_map(|index| match self.data_requests.get(index).unwrap() {
+                DataRequest::Finalized(dr) => Some(dr.summarize_claimable(self, &account_id)),
+                DataRequest::Active(_) => None,
+            })
+            .filter(|claimable| {
+                claimable.stake_token_payout.0 > 0 || claimable.payment_token_payout.0 > 0
+            })
+            .collect()
+    }
 }
 
 impl Contract {
+    /**
+     * @notice Stores a new data request for `sender` of which the validity bond and `resolution_fee` have been paid
+     * @returns the id of the data request
+     */
+    pub fn dr_create(
+        &mut self,
+        sender: &AccountId,
+        config: &OracleConfig,
+        resolution_fee: Balance,
+        payload: NewDataRequestArgs,
+        settings: DataRequestSettings,
+    ) -> u64 {
+        let requester = self.whitelist.whitelist_get_expect(sender);
+        let id = self.data_requests.len() as u64;
+        if let Some(bonds) = &settings.bond_settings {
+            self.bond_settings.insert(&id, bonds);
+        }
+        if let Some(bounds) = &settings.numeric_bounds {
+            self.numeric_bounds.insert(&id, bounds);
+        }
+        if let Some(format) = &settings.answer_format {
+            self.answer_formats.insert(&id, format);
+        }
+        if let Some(commit_reveal) = &settings.commit_reveal {
+            self.init_commit_round(id, commit_reveal);
+        }
+        if let Some(quorum) = &settings.provider_quorum {
+            self.init_provider_quorum(id, quorum);
+        }
+        self.init_stake_totals(id);
+        let dr = ActiveDataRequest::new(
+            requester,
+            id,
+            self.configs.len() - 1, // dr's config id
+            config,
+            resolution_fee,
+            payload,
+        );
+
+        logger::log_new_data_request(
+            &dr,
+            resolution_fee,
+            self.numeric_bounds.get(&id),
+            self.answer_formats.get(&id),
+            self.provider_quorums.get(&id),
+        );
+        self.data_requests.push(&DataRequest::Active(dr));
+        let expiry: u64 = self.extended_config.request_expiry.into();
+        self.request_expiries
+            .insert(&id, &(env::block_timestamp() + expiry));
+        id
+    }
+
     /**
      * @notice Transforms a data request struct into another struct with Serde serialization
      */
@@ -796,106 +1850,25 @@ impl Contract {
 #[cfg(test)]
 mod mock_token_basic_tests {
     use super::*;
+    use crate::test_utils::*;
     use flux_sdk::{
-        config::{FeeConfig, OracleConfig},
+        config::OracleConfig,
         data_request::Source,
-        outcome::AnswerType,
+        outcome::{AnswerNumberType, AnswerType},
         requester::Requester,
         resolution_window::ResolutionWindow,
     };
-    use near_sdk::{testing_env, MockedBlockchain, VMContext};
-
-    fn alice() -> AccountId {
-        "alice.near".to_string()
-    }
-
-    fn bob() -> AccountId {
-        "bob.near".to_string()
-    }
-
-    fn carol() -> AccountId {
-        "carol.near".to_string()
-    }
-
-    fn dave() -> AccountId {
-        "dave.near".to_string()
-    }
-
-    fn token() -> AccountId {
-        "token.near".to_string()
-    }
-
-    fn gov() -> AccountId {
-        "gov.near".to_string()
-    }
+    use near_sdk::{testing_env, MockedBlockchain, PromiseResult, VMContext};
 
     fn sum_claim_res(claim_res: ClaimRes) -> u128 {
         claim_res.payment_token_payout + claim_res.stake_token_payout
     }
 
-    fn registry_entry(account: AccountId) -> Requester {
-        Requester {
-            contract_name: account.clone(),
-            account_id: account.clone(),
-            stake_multiplier: None,
-            code_base_url: None,
-        }
-    }
-
     fn finalize(contract: &mut Contract, dr_id: u64) -> &mut Contract {
         contract.dr_finalize(dr_id.into());
         contract
     }
 
-    fn new_config(final_arb_amount: u128) -> OracleConfig {
-        OracleConfig {
-            gov: gov(),
-            final_arbitrator: alice(),
-            payment_token: token(),
-            stake_token: token(),
-            validity_bond: U128(100),
-            max_outcomes: 8,
-            default_challenge_window_duration: U64(1000),
-            min_initial_challenge_window_duration: U64(1000),
-            final_arbitrator_invoke_amount: U128(final_arb_amount),
-            fee: FeeConfig {
-                flux_market_cap: U128(50000),
-                total_value_staked: U128(10000),
-                resolution_fee_percentage: 10_000,
-            },
-            min_resolution_bond: U128(100),
-        }
-    }
-    
-    fn config() -> OracleConfig {
-        new_config(1_000_000)
-    }
-    
-    fn config_for_quick_final_arb() -> OracleConfig {
-        new_config(250)
-    }
-
-    fn get_context(predecessor_account_id: AccountId) -> VMContext {
-        VMContext {
-            current_account_id: token(),
-            signer_account_id: bob(),
-            signer_account_pk: vec![0, 1, 2],
-            predecessor_account_id,
-            input: vec![],
-            block_index: 0,
-            block_timestamp: 0,
-            account_balance: 10000 * 10u128.pow(24),
-            account_locked_balance: 0,
-            storage_usage: 10u64.pow(6),
-            attached_deposit: 1000 * 10u128.pow(24),
-            prepaid_gas: 10u64.pow(18),
-            random_seed: vec![0, 1, 2],
-            is_view: false,
-            output_data_receivers: vec![],
-            epoch_height: 0,
-        }
-    }
-
     #[test]
     #[should_panic(expected = "Invalid outcome list either exceeds min of: 2 or max of 8")]
     fn dr_new_single_outcome() {
@@ -1142,9 +2115,10 @@ mod mock_token_basic_tests {
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
         let mut contract = Contract::new(whitelist, config());
 
+        // validity bond of 100 + resolution fee of 8
         let amount: Balance = contract.dr_new(
             bob(),
-            100,
+            108,
             NewDataRequestArgs {
                 sources: Some(Vec::new()),
                 outcomes: None,
@@ -1159,9 +2133,10 @@ mod mock_token_basic_tests {
     }
 
     fn dr_new(contract: &mut Contract) {
+        // validity bond + the resolution fee of 8 `config()` charges
         contract.dr_new(
             bob(),
-            100,
+            108,
             NewDataRequestArgs {
                 sources: Some(Vec::new()),
                 outcomes: Some(vec!["a".to_string(), "b".to_string()].to_vec()),
@@ -1431,6 +2406,232 @@ mod mock_token_basic_tests {
         contract.dr_finalize(U64(0));
     }
 
+    #[test]
+    fn dr_cancel_success() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel(U64(0));
+        assert!(contract.dr_is_cancelled(U64(0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "This function can only be called by bob.near")]
+    fn dr_cancel_not_requester() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+
+        testing_env!(get_context(carol()));
+        contract.dr_cancel(U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Error DataRequest has already been staked on")]
+    fn dr_cancel_after_stake() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        contract.dr_stake(
+            alice(),
+            10,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+            },
+        );
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel(U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Error DataRequest is cancelled")]
+    fn dr_stake_cancelled() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel(U64(0));
+
+        testing_env!(get_context(token()));
+        contract.dr_stake(
+            alice(),
+            10,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+            },
+        );
+    }
+
+    #[test]
+    fn dr_finalize_expired_success() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        let expiry = contract.get_request_expiry(U64(0)).unwrap();
+        assert_eq!(expiry, contract.extended_config.request_expiry);
+
+        let mut ct: VMContext = get_context(alice());
+        ct.block_timestamp = expiry.into();
+        testing_env!(ct);
+        contract.dr_finalize_expired(U64(0));
+
+        let request: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
+        assert_eq!(request.finalized_outcome, Outcome::Invalid);
+        // the requester is told it gets its validity bond back
+        assert!(
+            contract
+                .get_outcome_delivery(U64(0))
+                .unwrap()
+                .validity_bond_returned
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Error DataRequest has not expired yet")]
+    fn dr_finalize_expired_too_early() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+
+        let mut ct: VMContext = get_context(alice());
+        ct.block_timestamp = u64::from(contract.extended_config.request_expiry) - 1;
+        testing_env!(ct);
+        contract.dr_finalize_expired(U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Error DataRequest has already been staked on")]
+    fn dr_finalize_expired_staked() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        contract.dr_stake(
+            alice(),
+            10,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+            },
+        );
+
+        let mut ct: VMContext = get_context(alice());
+        ct.block_timestamp = contract.extended_config.request_expiry.into();
+        testing_env!(ct);
+        contract.dr_finalize_expired(U64(0));
+    }
+
+    #[test]
+    fn dr_top_up_success() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        let bond = contract
+            .dr_get_expect_active(U64(0))
+            .calc_resolution_bond(&contract);
+
+        contract.dr_top_up(alice(), 500, TopUpDataRequestArgs { id: U64(0) });
+        contract.dr_top_up(carol(), 100, TopUpDataRequestArgs { id: U64(0) });
+
+        let dr = contract.dr_get_expect_active(U64(0));
+        assert_eq!(dr.request_config.paid_fee, 600);
+        assert_eq!(dr.summarize_dr().request_config.paid_fee, U128(600));
+        // top-ups don't raise the resolution bond
+        assert_eq!(dr.calc_resolution_bond(&contract), bond);
+    }
+
+    #[test]
+    #[should_panic(expected = "This function can only be called by token.near")]
+    fn dr_top_up_non_payment_token() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+
+        testing_env!(get_context(alice()));
+        contract.dr_top_up(alice(), 500, TopUpDataRequestArgs { id: U64(0) });
+    }
+
+    #[test]
+    fn dr_withdraw_top_up_after_cancel() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        contract.dr_top_up(alice(), 500, TopUpDataRequestArgs { id: U64(0) });
+        contract.dr_top_up(carol(), 100, TopUpDataRequestArgs { id: U64(0) });
+        contract.dr_top_up(alice(), 50, TopUpDataRequestArgs { id: U64(0) });
+        assert_eq!(contract.get_fee_top_up(U64(0), alice()), U128(550));
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel(U64(0));
+
+        testing_env!(get_context(alice()));
+        contract.dr_withdraw_top_up(U64(0));
+        assert_eq!(contract.get_fee_top_up(U64(0), alice()), U128(0));
+        // the other senders withdraw their own top-ups
+        assert_eq!(contract.get_fee_top_up(U64(0), carol()), U128(100));
+    }
+
+    #[test]
+    fn dr_withdraw_top_up_after_expiry() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        contract.dr_top_up(carol(), 100, TopUpDataRequestArgs { id: U64(0) });
+
+        let mut ct: VMContext = get_context(carol());
+        ct.block_timestamp = contract.extended_config.request_expiry.into();
+        testing_env!(ct);
+        contract.dr_finalize_expired(U64(0));
+        contract.dr_withdraw_top_up(U64(0));
+        assert_eq!(contract.get_fee_top_up(U64(0), carol()), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Error DataRequest fee can't be refunded")]
+    fn dr_withdraw_top_up_active() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        contract.dr_top_up(alice(), 500, TopUpDataRequestArgs { id: U64(0) });
+
+        testing_env!(get_context(alice()));
+        contract.dr_withdraw_top_up(U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "No fee top-up to withdraw")]
+    fn dr_withdraw_top_up_twice() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        contract.dr_top_up(alice(), 500, TopUpDataRequestArgs { id: U64(0) });
+
+        testing_env!(get_context(bob()));
+        contract.dr_cancel(U64(0));
+
+        testing_env!(get_context(alice()));
+        contract.dr_withdraw_top_up(U64(0));
+        contract.dr_withdraw_top_up(U64(0));
+    }
+
     #[test]
     #[should_panic(expected = "Error no bonded outcome, `DataRequest` still in progress")]
     fn dr_finalize_no_resolutions() {
@@ -1538,6 +2739,29 @@ mod mock_token_basic_tests {
         finalize(contract, 0);
     }
 
+    #[test]
+    #[should_panic(expected = "not enough gas to call set_outcome on the requester")]
+    fn dr_finalize_not_enough_gas() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        contract.dr_stake(
+            alice(),
+            200,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+            },
+        );
+
+        let mut ct: VMContext = get_context(token());
+        ct.block_timestamp = 1501;
+        ct.prepaid_gas = whitelist::DEFAULT_SET_OUTCOME_GAS;
+        testing_env!(ct);
+        contract.dr_finalize(U64(0));
+    }
+
     #[test]
     #[should_panic(expected = "ERR_DATA_REQUEST_NOT_FOUND")]
     fn dr_unstake_invalid_id() {
@@ -1701,32 +2925,248 @@ mod mock_token_basic_tests {
     }
 
     #[test]
-    #[should_panic(expected = "Error no DataRequest with this id exists")]
-    fn dr_claim_invalid_id() {
-        testing_env!(get_context(token()));
-        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
-        let mut contract = Contract::new(whitelist, config());
-
-        contract.dr_claim(alice(), U64(0));
-    }
-
-    #[test]
-    fn dr_claim_success() {
+    fn dr_unstake_multiple() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
-        let mut contract = Contract::new(whitelist, config());
+        let mut contract = Contract::new(whitelist, zero_fee_config());
         dr_new(&mut contract);
-        dr_finalize(
-            &mut contract,
-            data_request::Outcome::Answer(AnswerType::String("a".to_string())),
-        );
-
-        contract.dr_claim(alice(), U64(0));
-    }
 
-    #[test]
-    fn d_claim_single() {
-        testing_env!(get_context(token()));
+        let outcome = data_request::Outcome::Answer(AnswerType::String("b".to_string()));
+        contract.dr_stake(
+            alice(),
+            10,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: outcome.clone(),
+            },
+        );
+        contract.dr_stake(
+            bob(),
+            20,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: outcome.clone(),
+            },
+        );
+
+        testing_env!(get_context(alice()));
+        contract.dr_unstake(U64(0), 0, outcome.clone(), U128(3));
+        contract.dr_unstake(U64(0), 0, outcome.clone(), U128(4));
+        testing_env!(get_context(bob()));
+        contract.dr_unstake(U64(0), 0, outcome.clone(), U128(5));
+
+        assert_eq!(
+            contract.get_user_outcome_stake(U64(0), 0, alice(), outcome.clone()),
+            U128(3)
+        );
+        assert_eq!(
+            contract.get_user_outcome_stake(U64(0), 0, bob(), outcome.clone()),
+            U128(15)
+        );
+        assert_eq!(contract.get_outcome_stake(U64(0), 0, outcome), U128(18));
+    }
+
+    #[test]
+    fn dr_unstake_transfer_failed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+
+        let outcome = data_request::Outcome::Answer(AnswerType::String("b".to_string()));
+        contract.dr_stake(
+            alice(),
+            10,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: outcome.clone(),
+            },
+        );
+
+        testing_env!(get_context(alice()));
+        contract.dr_unstake(U64(0), 0, outcome.clone(), U128(4));
+        assert_eq!(
+            contract.get_user_outcome_stake(U64(0), 0, alice(), outcome.clone()),
+            U128(6)
+        );
+
+        testing_env!(
+            get_context(token()),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        let transferred =
+            contract.dr_unstake_callback(U64(0), 0, alice(), outcome.clone(), U128(4));
+
+        assert!(!transferred);
+        assert_eq!(
+            contract.get_user_outcome_stake(U64(0), 0, alice(), outcome.clone()),
+            U128(10)
+        );
+        assert_eq!(contract.get_outcome_stake(U64(0), 0, outcome), U128(10));
+    }
+
+    #[test]
+    fn dr_unstake_transfer_failed_after_bond() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+
+        let outcome = data_request::Outcome::Answer(AnswerType::String("b".to_string()));
+        contract.dr_stake(
+            alice(),
+            10,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: outcome.clone(),
+            },
+        );
+
+        testing_env!(get_context(alice()));
+        contract.dr_unstake(U64(0), 0, outcome.clone(), U128(4));
+
+        // bob bonds the outcome before the failed transfer is resolved
+        testing_env!(get_context(token()));
+        contract.dr_stake(
+            bob(),
+            200,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: outcome.clone(),
+            },
+        );
+        assert_eq!(contract.get_outcome_stake(U64(0), 0, outcome.clone()), U128(200));
+
+        testing_env!(
+            get_context(token()),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        let transferred =
+            contract.dr_unstake_callback(U64(0), 0, alice(), outcome.clone(), U128(4));
+
+        assert!(!transferred);
+        assert_eq!(
+            contract.get_user_outcome_stake(U64(0), 0, alice(), outcome.clone()),
+            U128(6)
+        );
+        assert_eq!(contract.get_outcome_stake(U64(0), 0, outcome), U128(200));
+        let failed_payouts = contract.get_failed_payouts(alice());
+        assert_eq!(failed_payouts.len(), 1);
+        assert_eq!(failed_payouts[0].token, token());
+        assert_eq!(failed_payouts[0].amount, U128(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Error no DataRequest with this id exists")]
+    fn dr_claim_invalid_id() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+
+        contract.dr_claim(alice(), U64(0));
+    }
+
+    #[test]
+    fn dr_claim_success() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        dr_finalize(
+            &mut contract,
+            data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+        );
+
+        contract.dr_claim(alice(), U64(0));
+    }
+
+    fn dr_new_finalized(contract: &mut Contract, request_id: u64, token: AccountId) {
+        testing_env!(get_context(token.to_string()));
+        dr_new(contract);
+        contract.dr_stake(
+            alice(),
+            200,
+            StakeDataRequestArgs {
+                id: U64(request_id),
+                outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+            },
+        );
+
+        let mut ct: VMContext = get_context(token);
+        ct.block_timestamp = 1501;
+        testing_env!(ct);
+        finalize(contract, request_id);
+    }
+
+    #[test]
+    fn dr_claim_many_success() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_finalized(&mut contract, 0, token());
+        dr_new_finalized(&mut contract, 1, token());
+        // still active so it is skipped
+        dr_new(&mut contract);
+
+        let res = contract.dr_claim_many(alice(), vec![U64(0), U64(1), U64(2), U64(3)]);
+        assert!(matches!(res, PromiseOrValue::Promise(_)));
+
+        let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 0);
+        let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(1.into());
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 0);
+    }
+
+    #[test]
+    fn dr_claim_many_other_tokens() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_finalized(&mut contract, 0, token());
+
+        testing_env!(get_context(gov()));
+        let mut new_config = zero_fee_config();
+        new_config.payment_token = dave();
+        new_config.stake_token = dave();
+        contract.set_config(new_config);
+        dr_new_finalized(&mut contract, 1, dave());
+
+        let res = contract.dr_claim_many(alice(), vec![U64(0), U64(1)]);
+        assert!(matches!(res, PromiseOrValue::Promise(_)));
+        assert_eq!(contract.dr_resolve_claim_many(vec![U64(1)]), vec![U64(1)]);
+
+        let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(1.into());
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 200);
+    }
+
+    #[test]
+    fn dr_claim_many_out_of_gas() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_finalized(&mut contract, 0, token());
+
+        let mut ct: VMContext = get_context(token());
+        ct.prepaid_gas = 3 * GAS_BASE_RESOLVE_TRANSFER + 2 * GAS_BASE_TRANSFER;
+        testing_env!(ct);
+        match contract.dr_claim_many(alice(), vec![U64(0)]) {
+            PromiseOrValue::Value(unclaimed) => assert_eq!(unclaimed, vec![U64(0)]),
+            PromiseOrValue::Promise(_) => panic!("nothing should be paid out"),
+        }
+
+        let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 200);
+    }
+
+    #[test]
+    fn d_claim_single() {
+        testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
         let mut contract = Contract::new(whitelist, config());
         dr_new(&mut contract);
@@ -1737,7 +3177,7 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
         // validity bond
-        assert_eq!(sum_claim_res(d.claim(alice())), 200);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 208);
     }
 
     #[test]
@@ -1753,8 +3193,25 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
         // validity bond
-        assert_eq!(sum_claim_res(d.claim(alice())), 200);
-        assert_eq!(sum_claim_res(d.claim(alice())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 208);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 0);
+    }
+
+    #[test]
+    fn d_claim_single_without_fee() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new(&mut contract);
+        dr_finalize(
+            &mut contract,
+            data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+        );
+
+        let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
+        // the part of the payment above the validity bond is refunded, so only the bond is paid out
+        assert_eq!(d.paid_fee, 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 200);
     }
 
     #[test]
@@ -1773,8 +3230,9 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
 
-        // fees (100% of TVL)
-        assert_eq!(sum_claim_res(d.claim(alice())), 294);
+        // amount paid on top of the validity bond is refunded, so only the bond (2 * 2) is paid out
+        assert_eq!(d.paid_fee, 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 4);
     }
 
     #[test]
@@ -1799,8 +3257,8 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
         // validity bond
-        assert_eq!(sum_claim_res(d.claim(alice())), 100);
-        assert_eq!(sum_claim_res(d.claim(bob())), 100);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 104);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, bob())), 104);
     }
 
     #[test]
@@ -1827,8 +3285,151 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
         // validity bond + round 0 stake
-        assert_eq!(sum_claim_res(d.claim(alice())), 600);
-        assert_eq!(sum_claim_res(d.claim(bob())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 608);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, bob())), 0);
+    }
+
+    #[test]
+    fn d_claimable_matches_claim() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut config = zero_fee_config();
+        config.final_arbitrator_invoke_amount = U128(1000);
+        let mut contract = Contract::new(whitelist, config);
+        dr_new(&mut contract);
+
+        contract.dr_stake(
+            bob(),
+            200,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+            },
+        );
+        dr_finalize(
+            &mut contract,
+            data_request::Outcome::Answer(AnswerType::String("b".to_string())),
+        );
+
+        let expected = ClaimableSummary {
+            request_id: U64(0),
+            payment_token_payout: U128(0),
+            stake_token_payout: U128(600),
+        };
+        assert_eq!(contract.get_claimable(alice(), U64(0)), Some(expected));
+        // viewing does not change what can be claimed
+        assert_eq!(
+            contract
+                .get_claimable(alice(), U64(0))
+                .unwrap()
+                .stake_token_payout,
+            U128(600)
+        );
+
+        let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 600);
+        assert_eq!(
+            contract
+                .get_claimable(alice(), U64(0))
+                .unwrap()
+                .stake_token_payout,
+            U128(0)
+        );
+        assert_eq!(
+            contract
+                .get_claimable(bob(), U64(0))
+                .unwrap()
+                .stake_token_payout,
+            U128(0)
+        );
+    }
+
+    #[test]
+    fn g_claimables() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_finalized(&mut contract, 0, token());
+        dr_new_finalized(&mut contract, 1, token());
+        dr_new(&mut contract);
+
+        assert_eq!(contract.get_claimable(alice(), U64(2)), None);
+        assert_eq!(contract.get_claimable(alice(), U64(3)), None);
+        assert_eq!(contract.get_claimables(alice(), U64(0), U64(10)).len(), 2);
+        assert_eq!(contract.get_claimables(alice(), U64(1), U64(1)).len(), 1);
+        assert!(contract.get_claimables(bob(), U64(0), U64(10)).is_empty());
+
+        contract.dr_claim(alice(), U64(0));
+        let claimables = contract.get_claimables(alice(), U64(0), U64(10));
+        assert_eq!(claimables.len(), 1);
+        assert_eq!(claimables[0].request_id, U64(1));
+        assert_eq!(claimables[0].stake_token_payout, U128(200));
+    }
+
+    // creates a data request with `rounds` bonded rounds alternating between outcome "a" by alice and "b" by bob,
+    // "a" bonds the last round
+    fn dr_new_with_rounds(contract: &mut Contract, dr_id: u64, rounds: u16) {
+        testing_env!(get_context(token()));
+        dr_new(contract);
+
+        let mut bond_size = 200;
+        for round in 0..rounds {
+            // fresh context so the logs of all rounds don't exceed the log limit
+            testing_env!(get_context(token()));
+            let (sender, answer) = match (rounds - round) % 2 {
+                1 => (alice(), "a"),
+                _ => (bob(), "b"),
+            };
+            contract.dr_stake(
+                sender,
+                bond_size,
+                StakeDataRequestArgs {
+                    id: U64(dr_id),
+                    outcome: data_request::Outcome::Answer(AnswerType::String(answer.to_string())),
+                },
+            );
+            bond_size *= 2;
+        }
+
+        let mut ct: VMContext = get_context(token());
+        ct.block_timestamp = 1501;
+        testing_env!(ct);
+        finalize(contract, dr_id);
+    }
+
+    // @returns the gas used by alice's claim and what she got paid out
+    fn claim_gas(contract: &mut Contract, dr_id: u64) -> (u64, u128) {
+        let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(dr_id.into());
+        let gas_before = env::used_gas();
+        let payout = sum_claim_res(d.claim(contract, alice()));
+        (env::used_gas() - gas_before, payout)
+    }
+
+    #[test]
+    fn d_claim_gas_flat_over_rounds() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut config = zero_fee_config();
+        config.final_arbitrator_invoke_amount = U128(u128::MAX);
+        let mut contract = Contract::new(whitelist, config);
+        dr_new_with_rounds(&mut contract, 0, 3);
+        dr_new_with_rounds(&mut contract, 1, 33);
+
+        let (few_rounds_gas, few_rounds_payout) = claim_gas(&mut contract, 0);
+        let (many_rounds_gas, many_rounds_payout) = claim_gas(&mut contract, 1);
+
+        // alice bonded 200 + 800 and gets bob's 400
+        assert_eq!(few_rounds_payout, 1400);
+        // alice gets all bonded stake of rounds 0 to 32
+        assert_eq!(many_rounds_payout, (200u128 << 33) - 200);
+        // iterating over all windows, as claims of data requests without stake totals do, grows with the rounds
+        let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(1.into());
+        let gas_before = env::used_gas();
+        d.claim_from_windows(&contract, &bob());
+        let iterate_windows_gas = env::used_gas() - gas_before;
+
+        assert!(many_rounds_gas < few_rounds_gas * 3 / 2);
+        assert!(iterate_windows_gas > 5 * many_rounds_gas);
     }
 
     #[test]
@@ -1863,9 +3464,9 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
         // validity bond + round 0 stake
-        assert_eq!(sum_claim_res(d.claim(alice())), 450);
-        assert_eq!(sum_claim_res(d.claim(bob())), 0);
-        assert_eq!(sum_claim_res(d.claim(carol())), 150);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 456);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, bob())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, carol())), 152);
     }
 
     #[test]
@@ -1900,10 +3501,10 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
         // round 1 stake
-        assert_eq!(sum_claim_res(d.claim(alice())), 1120);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 1126);
         // validity bond
-        assert_eq!(sum_claim_res(d.claim(bob())), 280);
-        assert_eq!(sum_claim_res(d.claim(carol())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, bob())), 281);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, carol())), 0);
     }
 
     #[test]
@@ -1946,12 +3547,12 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
         // round 1 stake
-        assert_eq!(sum_claim_res(d.claim(alice())), 1120);
-        // 50% of validity bond
-        assert_eq!(sum_claim_res(d.claim(bob())), 140);
-        assert_eq!(sum_claim_res(d.claim(carol())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 1126);
+        // 50% of validity bond, their 0.8 share of the fee rounds down to 0
+        assert_eq!(sum_claim_res(d.claim(&mut contract, bob())), 140);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, carol())), 0);
         // 50% of validity bond
-        assert_eq!(sum_claim_res(d.claim(dave())), 140);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, dave())), 140);
     }
 
     #[test]
@@ -1994,19 +3595,19 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
         // 5/8 of round 1 stake
-        assert_eq!(sum_claim_res(d.claim(alice())), 700);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 704);
         // validity bond
-        assert_eq!(sum_claim_res(d.claim(bob())), 280);
-        assert_eq!(sum_claim_res(d.claim(carol())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, bob())), 281);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, carol())), 0);
         // 3/8 of round 1 stake
-        assert_eq!(sum_claim_res(d.claim(dave())), 420);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, dave())), 422);
     }
 
     #[test]
     fn d_claim_final_arb() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
-        let mut contract = Contract::new(whitelist, config_for_quick_final_arb());
+        let mut contract = Contract::new(whitelist, new_config(250));
         // needed for final arb function
         dr_new(&mut contract);
 
@@ -2035,8 +3636,8 @@ mod mock_token_basic_tests {
         );
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
-        assert_eq!(sum_claim_res(d.claim(alice())), 600);
-        assert_eq!(sum_claim_res(d.claim(bob())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 608);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, bob())), 0);
     }
 
     #[test]
@@ -2083,10 +3684,10 @@ mod mock_token_basic_tests {
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
         // validity bond
-        assert_eq!(sum_claim_res(d.claim(alice())), 280);
-        assert_eq!(sum_claim_res(d.claim(bob())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 281);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, bob())), 0);
         // round 1 funds
-        assert_eq!(sum_claim_res(d.claim(carol())), 1120);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, carol())), 1126);
     }
 
     #[test]
@@ -2132,10 +3733,10 @@ mod mock_token_basic_tests {
         );
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
-        assert_eq!(sum_claim_res(d.claim(alice())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 0);
         // validity bond (100), round0 (200), round2 (800)
-        assert_eq!(sum_claim_res(d.claim(bob())), 1400);
-        assert_eq!(sum_claim_res(d.claim(carol())), 0);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, bob())), 1408);
+        assert_eq!(sum_claim_res(d.claim(&mut contract, carol())), 0);
     }
 
     #[test]
@@ -2143,7 +3744,7 @@ mod mock_token_basic_tests {
     fn dr_final_arb_invoked() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
-        let mut contract = Contract::new(whitelist, config_for_quick_final_arb());
+        let mut contract = Contract::new(whitelist, new_config(250));
         dr_new(&mut contract);
 
         contract.dr_stake(
@@ -2231,7 +3832,7 @@ mod mock_token_basic_tests {
     fn dr_final_arb_twice() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
-        let mut contract = Contract::new(whitelist, config_for_quick_final_arb());
+        let mut contract = Contract::new(whitelist, new_config(250));
         // needed for final arb function
         dr_new(&mut contract);
 
@@ -2268,7 +3869,7 @@ mod mock_token_basic_tests {
     fn dr_final_arb_execute() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
-        let mut contract = Contract::new(whitelist, config_for_quick_final_arb());
+        let mut contract = Contract::new(whitelist, new_config(250));
         // needed for final arb function
         dr_new(&mut contract);
 
@@ -2331,16 +3932,16 @@ mod mock_token_basic_tests {
             stake_multiplier: None,
             code_base_url: None,
         };
-        let fixed_fee = 20;
         let whitelist = Some(vec![bob_requester, registry_entry(carol())]);
         let mut config = config();
         config.min_resolution_bond = U128(2);
-        let validity_bond = 2;
-        config.validity_bond = U128(validity_bond);
+        let validity_bond = 100;
+        // 100 * 10% * (50000 - 10000) / 50000
+        let fee = 8;
         let mut contract = Contract::new(whitelist, config);
         contract.dr_new(
             bob(),
-            fixed_fee + validity_bond,
+            fee + validity_bond,
             NewDataRequestArgs {
                 sources: Some(Vec::new()),
                 outcomes: Some(vec!["a".to_string(), "b".to_string()].to_vec()),
@@ -2357,48 +3958,20 @@ mod mock_token_basic_tests {
         );
 
         let mut d: FinalizedDataRequest = contract.dr_get_expect_finalized(0.into());
-        assert_eq!(sum_claim_res(d.claim(alice())), 60);
-    }
-
-    #[test]
-    fn dr_get_methods() {
-        testing_env!(get_context(token()));
-        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
-        let mut contract = Contract::new(whitelist, config());
-        dr_new(&mut contract);
-        dr_new(&mut contract);
-        dr_new(&mut contract);
-
-        match contract.get_latest_request().unwrap() {
-            DataRequestSummary::Active(s) => assert_eq!(s.id, U64(2)),
-            DataRequestSummary::Finalized(_) => panic!("expected `DataRequest` to be active"),
-        }
-        match contract.get_request_by_id(U64(1)).unwrap() {
-            DataRequestSummary::Active(s) => assert_eq!(s.id, U64(1)),
-            DataRequestSummary::Finalized(_) => panic!("expected `DataRequest` to be active"),
-        }
-        match &contract.get_requests(U64(0), U64(1))[0] {
-            DataRequestSummary::Active(s) => assert_eq!(s.id, U64(0)),
-            DataRequestSummary::Finalized(_) => panic!("expected `DataRequest` to be active"),
-        }
-        assert_eq!(contract.get_requests(U64(1), U64(1)).len(), 1);
-        assert_eq!(contract.get_requests(U64(1), U64(2)).len(), 2);
-        assert_eq!(contract.get_requests(U64(0), U64(3)).len(), 3);
+        assert_eq!(d.paid_fee, fee);
+        // bond of 200 + fee
+        assert_eq!(sum_claim_res(d.claim(&mut contract, alice())), 208);
     }
 
     #[test]
-    #[should_panic(expected = "Oracle is paused")]
-    fn dr_pausable() {
+    #[should_panic(expected = "Resolution fee of 8 not reached, received only 7")]
+    fn dr_new_fee_underpaid() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
         let mut contract = Contract::new(whitelist, config());
-
-        testing_env!(get_context(gov()));
-        contract.toggle_pause();
-
         contract.dr_new(
             bob(),
-            100,
+            107,
             NewDataRequestArgs {
                 sources: Some(Vec::new()),
                 outcomes: None,
@@ -2412,38 +3985,85 @@ mod mock_token_basic_tests {
     }
 
     #[test]
-    fn dr_new_first_party_oracle_fetch_test() {
+    fn dr_new_fee_overpaid_refund() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
         let mut contract = Contract::new(whitelist, config());
-        let outcome = Outcome::Answer(AnswerType::String("1_000_000".to_string()));
-
-        contract.dr_new(
+        let unspent = contract.dr_new(
             bob(),
-            100,
+            150,
             NewDataRequestArgs {
                 sources: Some(Vec::new()),
                 outcomes: None,
                 challenge_period: U64(1500),
                 description: Some("a".to_string()),
-                tags: vec![],
+                tags: vec!["1".to_string()],
                 data_type: data_request::DataRequestDataType::String,
-                provider: Some(bob()),
+                provider: None,
             },
         );
+        assert_eq!(unspent, 30);
+        assert_eq!(
+            contract
+                .dr_get_expect_active(0.into())
+                .request_config
+                .paid_fee,
+            20
+        );
+    }
 
-        testing_env!(get_context(bob()));
-        contract.dr_finalize_by_provider(U64(0), outcome);
+    #[test]
+    fn dr_get_methods() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+        dr_new(&mut contract);
+
+        match contract.get_latest_request().unwrap() {
+            DataRequestSummary::Active(s) => assert_eq!(s.id, U64(2)),
+            DataRequestSummary::Finalized(_) => panic!("expected `DataRequest` to be active"),
+        }
+        match contract.get_request_by_id(U64(1)).unwrap() {
+            DataRequestSummary::Active(s) => assert_eq!(s.id, U64(1)),
+            DataRequestSummary::Finalized(_) => panic!("expected `DataRequest` to be active"),
+        }
+        match &contract.get_requests(U64(0), U64(1))[0] {
+            DataRequestSummary::Active(s) => assert_eq!(s.id, U64(0)),
+            DataRequestSummary::Finalized(_) => panic!("expected `DataRequest` to be active"),
+        }
+        assert_eq!(contract.get_requests(U64(1), U64(1)).len(), 1);
+        assert_eq!(contract.get_requests(U64(1), U64(2)).len(), 2);
+        assert_eq!(contract.get_requests(U64(0), U64(3)).len(), 3);
     }
 
     #[test]
-    #[should_panic(expected = "error this is not a provider data request")]
-    fn dr_finalize_non_provider_request() {
+    #[should_panic(expected = "Oracle is paused")]
+    fn dr_pausable() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
         let mut contract = Contract::new(whitelist, config());
-        let outcome = Outcome::Answer(AnswerType::String("1_000_000".to_string()));
+
+        testing_env!(get_context(gov()));
+        contract.toggle_pause();
 
+        contract.dr_new(
+            bob(),
+            100,
+            NewDataRequestArgs {
+                sources: Some(Vec::new()),
+                outcomes: None,
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
+                data_type: data_request::DataRequestDataType::String,
+                provider: None,
+            },
+        );
+    }
+
+    fn dr_new_provider(contract: &mut Contract, provider: Option<AccountId>) {
         contract.dr_new(
             bob(),
             100,
@@ -2454,23 +4074,242 @@ mod mock_token_basic_tests {
                 description: Some("a".to_string()),
                 tags: vec![],
                 data_type: data_request::DataRequestDataType::String,
+                provider,
+            },
+        );
+    }
+
+    // @returns amount of tokens that didn't get bonded
+    fn propose(contract: &mut Contract, provider: AccountId, answer: &str, amount: u128) -> u128 {
+        match contract.dr_propose_by_provider(
+            provider,
+            amount,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: Outcome::Answer(AnswerType::String(answer.to_string())),
+            },
+        ) {
+            PromiseOrValue::Value(unspent) => unspent.into(),
+            PromiseOrValue::Promise(_) => panic!("expected the unspent stake"),
+        }
+    }
+
+    #[test]
+    fn dr_provider_proposal_finalizes() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider(&mut contract, Some(bob()));
+
+        // round 0 bonds 200, the surplus goes back to the provider
+        assert_eq!(propose(&mut contract, bob(), "1_000_000", 250), 50);
+        let request: ActiveDataRequest = contract.dr_get_expect_active(0.into());
+        assert_eq!(request.resolution_windows.len(), 2);
+        let round0: ResolutionWindow = request.resolution_windows.get(0).unwrap();
+        assert_eq!(round0.bond_size, 200);
+        assert_eq!(
+            round0.bonded_outcome,
+            Some(Outcome::Answer(AnswerType::String("1_000_000".to_string())))
+        );
+        let round1: ResolutionWindow = request.resolution_windows.get(1).unwrap();
+        assert_eq!(round1.end_time, 1500);
+
+        let mut ct: VMContext = get_context(token());
+        ct.block_timestamp = 1500;
+        testing_env!(ct);
+        contract.dr_finalize(U64(0));
+        let request = contract.dr_get_expect_finalized(U64(0));
+        assert_eq!(
+            request.finalized_outcome,
+            Outcome::Answer(AnswerType::String("1_000_000".to_string()))
+        );
+        assert_eq!(
+            contract
+                .get_claimable(bob(), U64(0))
+                .unwrap()
+                .stake_token_payout,
+            U128(200)
+        );
+    }
+
+    #[test]
+    fn dr_provider_proposal_challenged() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider(&mut contract, Some(bob()));
+
+        assert_eq!(propose(&mut contract, bob(), "1_000_000", 200), 0);
+        // carol bonds round 1 against the provider, opening round 2 for the default window duration
+        stake_string(&mut contract, carol(), "2_000_000", 400);
+        let request: ActiveDataRequest = contract.dr_get_expect_active(0.into());
+        assert_eq!(request.resolution_windows.len(), 3);
+        let round2: ResolutionWindow = request.resolution_windows.get(2).unwrap();
+        assert_eq!(round2.end_time, 1000);
+
+        let mut ct: VMContext = get_context(token());
+        ct.block_timestamp = 1000;
+        testing_env!(ct);
+        contract.dr_finalize(U64(0));
+        let request = contract.dr_get_expect_finalized(U64(0));
+        assert_eq!(
+            request.finalized_outcome,
+            Outcome::Answer(AnswerType::String("2_000_000".to_string()))
+        );
+        assert_eq!(
+            contract
+                .get_claimable(bob(), U64(0))
+                .unwrap()
+                .stake_token_payout,
+            U128(0)
+        );
+        assert_eq!(
+            contract
+                .get_claimable(carol(), U64(0))
+                .unwrap()
+                .stake_token_payout,
+            U128(600)
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Error provider requests can only be staked on once the provider proposed an outcome"
+    )]
+    fn dr_stake_before_provider_proposal() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider(&mut contract, Some(bob()));
+
+        stake_string(&mut contract, carol(), "1_000_000", 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Provider bond of 200 not reached, received only 100")]
+    fn dr_provider_proposal_bond_not_reached() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider(&mut contract, Some(bob()));
+
+        // the resolution bond alone doesn't bond round 0
+        propose(&mut contract, bob(), "1_000_000", 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "error this is not a provider data request")]
+    fn dr_propose_non_provider_request() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider(&mut contract, None);
+
+        propose(&mut contract, carol(), "1_000_000", 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "this request can only be proposed by the provider")]
+    fn dr_propose_from_wrong_provider() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider(&mut contract, Some(bob()));
+
+        propose(&mut contract, carol(), "1_000_000", 100);
+    }
+
+    fn dr_new_with_bond_settings(contract: &mut Contract, bond_settings: BondSettings) {
+        contract.dr_new_with_settings(
+            bob(),
+            100,
+            NewDataRequestArgs {
+                sources: Some(Vec::new()),
+                outcomes: Some(vec!["a".to_string(), "b".to_string()].to_vec()),
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
+                data_type: data_request::DataRequestDataType::String,
                 provider: None,
             },
+            DataRequestSettings {
+                bond_settings: Some(bond_settings),
+                ..DataRequestSettings::default()
+            },
         );
+    }
 
-        testing_env!(get_context(carol()));
-        contract.dr_finalize_by_provider(U64(0), outcome);
+    #[test]
+    fn dr_stake_custom_bond_settings() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        let challenge_duration = 60 * 60 * 1_000_000_000;
+        dr_new_with_bond_settings(
+            &mut contract,
+            BondSettings {
+                initial_bond: Some(U128(150)),
+                bond_growth: Some(15_000),
+                challenge_duration: Some(U64(challenge_duration)),
+            },
+        );
+
+        contract.dr_stake(
+            alice(),
+            225,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+            },
+        );
+        contract.dr_stake(
+            bob(),
+            337,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: data_request::Outcome::Answer(AnswerType::String("b".to_string())),
+            },
+        );
+
+        let request: ActiveDataRequest = contract.dr_get_expect_active(0.into());
+        assert_eq!(request.resolution_windows.len(), 3);
+
+        let round0: ResolutionWindow = request.resolution_windows.get(0).unwrap();
+        assert_eq!(round0.end_time, 1500);
+        assert_eq!(round0.bond_size, 225);
+
+        let round1: ResolutionWindow = request.resolution_windows.get(1).unwrap();
+        assert_eq!(round1.end_time, 1500);
+        assert_eq!(round1.bond_size, 337);
+
+        let round2: ResolutionWindow = request.resolution_windows.get(2).unwrap();
+        assert_eq!(round2.end_time, challenge_duration);
+        assert_eq!(round2.bond_size, 505);
     }
 
     #[test]
-    #[should_panic(expected = "this request can only be finalized by the provider")]
-    fn dr_finalize_from_wrong_provider() {
+    #[should_panic(expected = "bond growth has to be between 15000 and 40000")]
+    fn dr_new_bond_settings_out_of_limits() {
         testing_env!(get_context(token()));
         let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
-        let mut contract = Contract::new(whitelist, config());
-        let outcome = Outcome::Answer(AnswerType::String("1_000_000".to_string()));
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_with_bond_settings(
+            &mut contract,
+            BondSettings {
+                initial_bond: None,
+                bond_growth: Some(10_000),
+                challenge_duration: None,
+            },
+        );
+    }
 
-        contract.dr_new(
+    #[test]
+    #[should_panic(expected = "ERR_OUTCOME_ABOVE_MAX")]
+    fn dr_stake_outside_numeric_bounds() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        contract.dr_new_with_settings(
             bob(),
             100,
             NewDataRequestArgs {
@@ -2478,13 +4317,518 @@ mod mock_token_basic_tests {
                 outcomes: None,
                 challenge_period: U64(1500),
                 description: Some("a".to_string()),
-                tags: vec![],
+                tags: vec!["1".to_string()],
+                data_type: data_request::DataRequestDataType::Number(U128(100)),
+                provider: None,
+            },
+            DataRequestSettings {
+                numeric_bounds: Some(NumericBounds {
+                    min: None,
+                    max: Some(numeric_bounds::SignedNumber {
+                        value: U128(1_000_000),
+                        negative: false,
+                    }),
+                    step: None,
+                    tolerance: None,
+                }),
+                ..DataRequestSettings::default()
+            },
+        );
+
+        contract.dr_stake(
+            alice(),
+            200,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: data_request::Outcome::Answer(AnswerType::Number(AnswerNumberType {
+                    value: U128(1_000_100),
+                    multiplier: U128(100),
+                    negative: false,
+                })),
+            },
+        );
+    }
+
+    fn number_outcome(value: u128) -> Outcome {
+        data_request::Outcome::Answer(AnswerType::Number(AnswerNumberType {
+            value: U128(value),
+            multiplier: U128(100),
+            negative: false,
+        }))
+    }
+
+    fn dr_new_with_tolerance(contract: &mut Contract, tolerance: u128) {
+        contract.dr_new_with_settings(
+            bob(),
+            100,
+            NewDataRequestArgs {
+                sources: Some(Vec::new()),
+                outcomes: None,
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
+                data_type: data_request::DataRequestDataType::Number(U128(100)),
+                provider: None,
+            },
+            DataRequestSettings {
+                numeric_bounds: Some(NumericBounds {
+                    min: None,
+                    max: None,
+                    step: None,
+                    tolerance: Some(U128(tolerance)),
+                }),
+                ..DataRequestSettings::default()
+            },
+        );
+    }
+
+    fn stake_number(contract: &mut Contract, account_id: AccountId, value: u128, amount: u128) {
+        contract.dr_stake(
+            account_id,
+            amount,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: number_outcome(value),
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Outcome is incompatible for this round")]
+    fn dr_stake_challenge_within_tolerance() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_with_tolerance(&mut contract, 5);
+
+        stake_number(&mut contract, alice(), 10000, 200);
+        stake_number(&mut contract, bob(), 10005, 400);
+    }
+
+    #[test]
+    fn dr_stake_matches_earliest_bonded_within_tolerance() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_with_tolerance(&mut contract, 5);
+
+        stake_number(&mut contract, alice(), 10000, 200);
+        stake_number(&mut contract, bob(), 10006, 400);
+        stake_number(&mut contract, carol(), 9998, 800);
+
+        let request: ActiveDataRequest = contract.dr_get_expect_active(0.into());
+        assert_eq!(request.resolution_windows.len(), 4);
+        let round2: ResolutionWindow = request.resolution_windows.get(2).unwrap();
+        assert_eq!(round2.bonded_outcome, Some(number_outcome(10000)));
+
+        let mut ct: VMContext = get_context(alice());
+        ct.block_timestamp = 1000;
+        testing_env!(ct);
+        contract.dr_finalize(U64(0));
+        let request = contract.dr_get_expect_finalized(U64(0));
+        assert_eq!(request.finalized_outcome, number_outcome(10000));
+    }
+
+    #[test]
+    fn dr_stake_same_round_within_tolerance() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_with_tolerance(&mut contract, 5);
+
+        stake_number(&mut contract, alice(), 10000, 100);
+        stake_number(&mut contract, bob(), 10003, 100);
+
+        let request: ActiveDataRequest = contract.dr_get_expect_active(0.into());
+        assert_eq!(request.resolution_windows.len(), 2);
+        let round0: ResolutionWindow = request.resolution_windows.get(0).unwrap();
+        assert_eq!(round0.bonded_outcome, Some(number_outcome(10000)));
+
+        let mut ct: VMContext = get_context(alice());
+        ct.block_timestamp = 1500;
+        testing_env!(ct);
+        contract.dr_finalize(U64(0));
+        let d = contract.dr_get_expect_finalized(U64(0));
+        assert_eq!(d.claimable(&contract, &alice()).stake_token_payout, 100);
+        assert_eq!(d.claimable(&contract, &bob()).stake_token_payout, 100);
+    }
+
+    #[test]
+    fn dr_final_arbitrator_finalize_within_tolerance() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config_for_quick_final_arb());
+        dr_new_with_tolerance(&mut contract, 5);
+
+        stake_number(&mut contract, alice(), 10000, 200);
+        // This round exceeds final arb limit
+        stake_number(&mut contract, bob(), 10010, 400);
+
+        testing_env!(get_context(alice()));
+        contract.dr_final_arbitrator_finalize(U64(0), number_outcome(10003));
+        let d = contract.dr_get_expect_finalized(U64(0));
+        assert_eq!(d.finalized_outcome, number_outcome(10000));
+        assert_eq!(d.claimable(&contract, &alice()).stake_token_payout, 600);
+    }
+
+    fn dr_new_multi_select(contract: &mut Contract) {
+        contract.dr_new_with_settings(
+            bob(),
+            100,
+            NewDataRequestArgs {
+                sources: Some(Vec::new()),
+                outcomes: Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]),
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
                 data_type: data_request::DataRequestDataType::String,
-                provider: Some(bob()),
+                provider: None,
+            },
+            DataRequestSettings {
+                answer_format: Some(AnswerFormat::MultiSelect),
+                ..DataRequestSettings::default()
             },
         );
+    }
 
-        testing_env!(get_context(carol()));
-        contract.dr_finalize_by_provider(U64(0), outcome);
+    fn stake_string(contract: &mut Contract, account_id: AccountId, answer: &str, amount: u128) {
+        contract.dr_stake(
+            account_id,
+            amount,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: data_request::Outcome::Answer(AnswerType::String(answer.to_string())),
+            },
+        );
+    }
+
+    #[test]
+    fn dr_stake_multi_select() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_multi_select(&mut contract);
+
+        stake_string(&mut contract, alice(), "0,2", 200);
+        stake_string(&mut contract, bob(), "2", 400);
+
+        let request: ActiveDataRequest = contract.dr_get_expect_active(0.into());
+        assert_eq!(request.resolution_windows.len(), 3);
+        let round1: ResolutionWindow = request.resolution_windows.get(1).unwrap();
+        assert_eq!(
+            round1.bonded_outcome,
+            Some(data_request::Outcome::Answer(AnswerType::String("2".to_string())))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_SELECTION")]
+    fn dr_stake_multi_select_not_canonical() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_multi_select(&mut contract);
+
+        stake_string(&mut contract, alice(), "2,0", 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Outcome is incompatible for this round")]
+    fn dr_stake_multi_select_same_selection() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_multi_select(&mut contract);
+
+        stake_string(&mut contract, alice(), "0,2", 200);
+        stake_string(&mut contract, bob(), "0,2", 400);
+    }
+
+    fn dr_new_commit_reveal(contract: &mut Contract) {
+        contract.dr_new_with_settings(
+            bob(),
+            100,
+            NewDataRequestArgs {
+                sources: Some(Vec::new()),
+                outcomes: Some(vec!["a".to_string(), "b".to_string()]),
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
+                data_type: data_request::DataRequestDataType::String,
+                provider: None,
+            },
+            DataRequestSettings {
+                commit_reveal: Some(CommitRevealSettings {
+                    commit_duration: U64(100),
+                    reveal_duration: U64(100),
+                }),
+                ..DataRequestSettings::default()
+            },
+        );
+    }
+
+    fn commit_stake(contract: &mut Contract, account_id: AccountId, answer: &str, amount: u128) {
+        let outcome = data_request::Outcome::Answer(AnswerType::String(answer.to_string()));
+        contract.dr_commit_stake(
+            account_id.to_string(),
+            amount,
+            CommitStakeArgs {
+                id: U64(0),
+                commitment: commit_reveal::commitment(0, &account_id, &outcome, &account_id),
+            },
+        );
+    }
+
+    fn reveal_stake(contract: &mut Contract, account_id: AccountId, answer: &str) {
+        let mut ct: VMContext = get_context(account_id.to_string());
+        ct.block_timestamp = 100;
+        testing_env!(ct);
+        contract.dr_reveal_stake(
+            U64(0),
+            data_request::Outcome::Answer(AnswerType::String(answer.to_string())),
+            account_id,
+        );
+    }
+
+    #[test]
+    fn dr_settle_commits_bonds_leading_outcome() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_commit_reveal(&mut contract);
+
+        commit_stake(&mut contract, bob(), "b", 100);
+        commit_stake(&mut contract, alice(), "a", 250);
+        commit_stake(&mut contract, carol(), "a", 50);
+        reveal_stake(&mut contract, alice(), "a");
+        reveal_stake(&mut contract, bob(), "b");
+
+        let mut ct: VMContext = get_context(carol());
+        ct.block_timestamp = 200;
+        testing_env!(ct);
+        contract.dr_settle_commits(U64(0));
+
+        let request: ActiveDataRequest = contract.dr_get_expect_active(0.into());
+        assert_eq!(request.resolution_windows.len(), 2);
+        let round0: ResolutionWindow = request.resolution_windows.get(0).unwrap();
+        assert_eq!(
+            round0.bonded_outcome,
+            Some(data_request::Outcome::Answer(AnswerType::String("a".to_string())))
+        );
+        assert!(contract.get_commit_round(U64(0)).unwrap().settled);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error outcome and salt don't match the commitment")]
+    fn dr_reveal_copied_commitment() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_commit_reveal(&mut contract);
+
+        commit_stake(&mut contract, alice(), "a", 100);
+        // carol commits alice's commitment without knowing the outcome and salt behind it
+        let commitment = contract.get_commit_round(U64(0)).unwrap().commits[0]
+            .commitment
+            .to_string();
+        contract.dr_commit_stake(
+            carol(),
+            100,
+            CommitStakeArgs {
+                id: U64(0),
+                commitment,
+            },
+        );
+
+        // and reveals it with what alice revealed
+        reveal_stake(&mut contract, alice(), "a");
+        let mut ct: VMContext = get_context(carol());
+        ct.block_timestamp = 100;
+        testing_env!(ct);
+        contract.dr_reveal_stake(
+            U64(0),
+            data_request::Outcome::Answer(AnswerType::String("a".to_string())),
+            alice(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Error DataRequest is staked through commits until they are settled")]
+    fn dr_stake_before_commits_settled() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_commit_reveal(&mut contract);
+
+        stake_string(&mut contract, alice(), "a", 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error outcome and salt don't match the commitment")]
+    fn dr_reveal_stake_wrong_outcome() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_commit_reveal(&mut contract);
+
+        commit_stake(&mut contract, alice(), "a", 200);
+        reveal_stake(&mut contract, alice(), "b");
+    }
+
+    #[test]
+    #[should_panic(expected = "Error commit phase has ended")]
+    fn dr_commit_stake_after_commit_phase() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_commit_reveal(&mut contract);
+
+        let mut ct: VMContext = get_context(token());
+        ct.block_timestamp = 100;
+        testing_env!(ct);
+        commit_stake(&mut contract, alice(), "a", 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Minimum commit of 6 not reached, received only 5")]
+    fn dr_commit_stake_below_minimum() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_commit_reveal(&mut contract);
+
+        commit_stake(&mut contract, alice(), "a", 5);
+    }
+
+    fn dr_new_provider_quorum(contract: &mut Contract) {
+        contract.dr_new_with_settings(
+            bob(),
+            100,
+            NewDataRequestArgs {
+                sources: Some(Vec::new()),
+                outcomes: Some(vec!["a".to_string(), "b".to_string()]),
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
+                data_type: data_request::DataRequestDataType::String,
+                provider: None,
+            },
+            DataRequestSettings {
+                provider_quorum: Some(ProviderQuorumSettings {
+                    providers: vec![alice(), carol(), dave()],
+                    threshold: 2,
+                    submission_duration: U64(100),
+                    challenge_duration: U64(50),
+                }),
+                ..DataRequestSettings::default()
+            },
+        );
+    }
+
+    fn submit_provider_outcome(contract: &mut Contract, account_id: AccountId, answer: &str) {
+        testing_env!(get_context(account_id));
+        contract.dr_submit_provider_outcome(
+            U64(0),
+            data_request::Outcome::Answer(AnswerType::String(answer.to_string())),
+        );
+    }
+
+    #[test]
+    fn dr_provider_quorum_finalizes_agreed_outcome() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider_quorum(&mut contract);
+
+        submit_provider_outcome(&mut contract, alice(), "a");
+        submit_provider_outcome(&mut contract, carol(), "b");
+        assert_eq!(
+            contract.dr_get_expect_active(0.into()).resolution_windows.len(),
+            0
+        );
+        submit_provider_outcome(&mut contract, dave(), "a");
+
+        let request: ActiveDataRequest = contract.dr_get_expect_active(0.into());
+        assert_eq!(request.resolution_windows.len(), 2);
+        let round0: ResolutionWindow = request.resolution_windows.get(0).unwrap();
+        assert_eq!(round0.bond_size, 0);
+        assert_eq!(
+            round0.bonded_outcome,
+            Some(data_request::Outcome::Answer(AnswerType::String("a".to_string())))
+        );
+        let round1: ResolutionWindow = request.resolution_windows.get(1).unwrap();
+        assert_eq!(round1.bond_size, 100);
+        assert_eq!(round1.end_time, 50);
+
+        let mut ct: VMContext = get_context(token());
+        ct.block_timestamp = 50;
+        testing_env!(ct);
+        contract.dr_finalize(U64(0));
+        let request = contract.dr_get_expect_finalized(U64(0));
+        assert_eq!(
+            request.finalized_outcome,
+            data_request::Outcome::Answer(AnswerType::String("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn dr_provider_quorum_disputed() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider_quorum(&mut contract);
+
+        submit_provider_outcome(&mut contract, alice(), "a");
+        submit_provider_outcome(&mut contract, carol(), "a");
+        testing_env!(get_context(token()));
+        stake_string(&mut contract, bob(), "b", 100);
+
+        let request: ActiveDataRequest = contract.dr_get_expect_active(0.into());
+        assert_eq!(request.resolution_windows.len(), 3);
+        let round2: ResolutionWindow = request.resolution_windows.get(2).unwrap();
+        assert_eq!(round2.bond_size, 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error DataRequest is open for provider submissions")]
+    fn dr_stake_before_provider_agreement() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider_quorum(&mut contract);
+
+        submit_provider_outcome(&mut contract, alice(), "a");
+        testing_env!(get_context(token()));
+        stake_string(&mut contract, bob(), "b", 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error bob.near is not a provider of this DataRequest")]
+    fn dr_submit_provider_outcome_non_provider() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider_quorum(&mut contract);
+
+        submit_provider_outcome(&mut contract, bob(), "a");
+    }
+
+    #[test]
+    fn dr_withdraw_top_up_after_provider_quorum() {
+        testing_env!(get_context(token()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        dr_new_provider_quorum(&mut contract);
+        contract.dr_top_up(dave(), 100, TopUpDataRequestArgs { id: U64(0) });
+
+        submit_provider_outcome(&mut contract, alice(), "a");
+        submit_provider_outcome(&mut contract, carol(), "a");
+        let mut ct: VMContext = get_context(dave());
+        ct.block_timestamp = 50;
+        testing_env!(ct);
+        contract.dr_finalize(U64(0));
+
+        contract.dr_withdraw_top_up(U64(0));
+        assert_eq!(contract.get_fee_top_up(U64(0), dave()), U128(0));
     }
 }
@@ -0,0 +1,370 @@
+use crate::data_request::DataRequestSettings;
+use crate::*;
+use flux_sdk::{
+    data_request::{DataRequestDataType, NewDataRequestArgs, Source},
+    types::WrappedBalance,
+};
+use near_sdk::{
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+};
+
+/// `NewDataRequestArgs` and `DataRequestSettings` every data request of a series is created with
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DataRequestTemplate {
+    pub sources: Option<Vec<Source>>,
+    pub outcomes: Option<Vec<String>>,
+    pub challenge_period: U64,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub data_type: DataRequestDataType,
+    pub provider: Option<AccountId>,
+    #[serde(default)]
+    pub settings: DataRequestSettings,
+}
+
+impl From<DataRequestTemplate> for NewDataRequestArgs {
+    fn from(template: DataRequestTemplate) -> Self {
+        Self {
+            sources: template.sources,
+            outcomes: template.outcomes,
+            challenge_period: template.challenge_period,
+            description: template.description,
+            tags: template.tags,
+            data_type: template.data_type,
+            provider: template.provider,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NewDataRequestSeriesArgs {
+    pub template: DataRequestTemplate,
+    pub interval: U64,           // time between two data requests in ns
+    pub count: u32,              // number of data requests in the series
+    pub start_time: Option<U64>, // when the first data request is due, defaults to now
+}
+
+/// Data requests with the same question opened at a fixed interval, paid for from an escrow funded up front
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DataRequestSeries {
+    pub id: U64,
+    pub requester: AccountId,
+    pub token: AccountId, // payment token the escrow is held in
+    pub template: DataRequestTemplate,
+    pub interval: U64,
+    pub next_at: U64,
+    pub remaining: u32,
+    pub escrow: WrappedBalance,
+}
+
+impl Contract {
+    /**
+     * @notice Opens a series of data requests paid for by `amount` of payment tokens sent by `sender`
+     * @returns amount of tokens that didn't go into the escrow
+     */
+    pub fn dr_new_series(
+        &mut self,
+        sender: AccountId,
+        amount: Balance,
+        payload: NewDataRequestSeriesArgs,
+    ) -> Balance {
+        self.assert_unpaused();
+        let config = self.get_config();
+        self.assert_whitelisted(sender.to_string());
+        self.assert_sender(&config.payment_token);
+        let args: NewDataRequestArgs = payload.template.clone().into();
+        self.dr_validate(&args);
+        self.dr_validate_settings(&args, &payload.template.settings);
+
+        let interval: u64 = payload.interval.into();
+        assert!(interval > 0, "interval has to be higher than 0");
+        assert!(payload.count > 0, "count has to be higher than 0");
+
+        let cost = u128::from(config.validity_bond) + fee_config::calc_resolution_fee(&config);
+        let required = cost * u128::from(payload.count);
+        assert!(
+            amount >= required,
+            "Series escrow of {} not reached, received only {}",
+            required,
+            amount
+        );
+
+        let series = DataRequestSeries {
+            id: U64(self.series.len()),
+            requester: sender,
+            token: config.payment_token,
+            template: payload.template,
+            interval: payload.interval,
+            next_at: payload
+                .start_time
+                .unwrap_or_else(|| U64(env::block_timestamp())),
+            remaining: payload.count,
+            escrow: U128(amount),
+        };
+        self.series.push(&series);
+        logger::log_data_request_series(&series);
+
+        0
+    }
+
+    fn series_get_expect(&self, series_id: U64) -> DataRequestSeries {
+        self.series
+            .get(series_id.into())
+            .expect("ERR_SERIES_NOT_FOUND")
+    }
+
+    // refunds what is left in the escrow of a series to its requester
+    fn refund_series_escrow(&self, series: &mut DataRequestSeries) {
+        let escrow: u128 = series.escrow.into();
+        if escrow > 0 {
+            self.payout(series.token.to_string(), series.requester.to_string(), escrow);
+            series.escrow = U128(0);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * @notice Opens the next data request of a series once it is due, paying its validity bond and fee from the
+     * escrow. Callable by anyone, the storage is paid by the series' requester. What is left in the escrow after the
+     * last data request is refunded
+     * @returns the id of the new data request
+     */
+    pub fn dr_spawn_due(&mut self, series_id: U64) -> U64 {
+        self.assert_unpaused();
+        let initial_storage = env::storage_usage();
+        let mut series = self.series_get_expect(series_id);
+        assert!(series.remaining > 0, "series has no data requests left");
+        assert!(
+            env::block_timestamp() >= u64::from(series.next_at),
+            "next data request of this series is not due yet"
+        );
+
+        let config = self.get_config();
+        assert_eq!(
+            config.payment_token, series.token,
+            "payment token changed since the series was funded"
+        );
+        let resolution_fee = fee_config::calc_resolution_fee(&config);
+        let cost = u128::from(config.validity_bond) + resolution_fee;
+        let escrow: u128 = series.escrow.into();
+        assert!(
+            escrow >= cost,
+            "series escrow of {} can't pay for the next data request costing {}",
+            escrow,
+            cost
+        );
+
+        // governance may have changed the bond limits since the series was opened
+        let settings = series.template.settings.clone();
+        self.dr_validate_settings(&series.template.clone().into(), &settings);
+
+        let account = self.get_storage_account(&series.requester);
+        let dr_id = self.dr_create(
+            &series.requester,
+            &config,
+            resolution_fee,
+            series.template.clone().into(),
+            settings,
+        );
+
+        series.escrow = U128(escrow - cost);
+        series.remaining -= 1;
+        series.next_at = U64(u64::from(series.next_at) + u64::from(series.interval));
+        if series.remaining == 0 {
+            self.refund_series_escrow(&mut series);
+        }
+
+        self.series.replace(series_id.into(), &series);
+        logger::log_data_request_series(&series);
+        self.use_storage(&series.requester, initial_storage, account.available);
+
+        U64(dr_id)
+    }
+
+    /**
+     * @notice Stops a series and refunds what is left in its escrow, only callable by the series' requester
+     */
+    pub fn dr_cancel_series(&mut self, series_id: U64) {
+        self.assert_unpaused();
+        let mut series = self.series_get_expect(series_id);
+        self.assert_sender(&series.requester);
+
+        series.remaining = 0;
+        self.refund_series_escrow(&mut series);
+        self.series.replace(series_id.into(), &series);
+        logger::log_data_request_series(&series);
+    }
+
+    pub fn get_series(&self, series_id: U64) -> Option<DataRequestSeries> {
+        self.series.get(series_id.into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use crate::answer_format::AnswerFormat;
+    use crate::storage_manager::StorageManager;
+    use crate::test_utils::*;
+    use near_sdk::{json_types::ValidAccountId, testing_env, MockedBlockchain, VMContext};
+    use std::convert::TryInto;
+
+    fn get_context(predecessor_account_id: AccountId, block_timestamp: u64) -> VMContext {
+        VMContext {
+            account_balance: 1000 * 10u128.pow(24),
+            attached_deposit: 10u128.pow(24),
+            ..get_context_at(predecessor_account_id, block_timestamp)
+        }
+    }
+
+    fn to_valid(account: AccountId) -> ValidAccountId {
+        account.try_into().expect("invalid account")
+    }
+
+    fn series_args(count: u32) -> NewDataRequestSeriesArgs {
+        NewDataRequestSeriesArgs {
+            template: DataRequestTemplate {
+                sources: Some(Vec::new()),
+                outcomes: Some(vec!["a".to_string(), "b".to_string()]),
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
+                data_type: DataRequestDataType::String,
+                provider: None,
+                settings: DataRequestSettings::default(),
+            },
+            interval: U64(100),
+            count,
+            start_time: Some(U64(50)),
+        }
+    }
+
+    fn series_contract(amount: Balance, count: u32) -> Contract {
+        testing_env!(get_context(bob(), 0));
+        let whitelist = Some(vec![registry_entry(bob())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config_for_quick_final_arb());
+        contract.storage_deposit(Some(to_valid(bob())));
+
+        testing_env!(get_context(token(), 0));
+        assert_eq!(contract.dr_new_series(bob(), amount, series_args(count)), 0);
+        contract
+    }
+
+    #[test]
+    fn series_spawn() {
+        let mut contract = series_contract(250, 2);
+        let series = contract.get_series(U64(0)).unwrap();
+        assert_eq!(series.escrow, U128(250));
+        assert_eq!(series.next_at, U64(50));
+
+        testing_env!(get_context(alice(), 50));
+        assert_eq!(contract.dr_spawn_due(U64(0)), U64(0));
+        let series = contract.get_series(U64(0)).unwrap();
+        assert_eq!(series.escrow, U128(150));
+        assert_eq!(series.remaining, 1);
+        assert_eq!(series.next_at, U64(150));
+        assert!(contract.dr_exists(U64(0)));
+
+        testing_env!(get_context(alice(), 150));
+        assert_eq!(contract.dr_spawn_due(U64(0)), U64(1));
+        // leftover is refunded after the last data request
+        let series = contract.get_series(U64(0)).unwrap();
+        assert_eq!(series.escrow, U128(0));
+        assert_eq!(series.remaining, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "next data request of this series is not due yet")]
+    fn series_spawn_not_due() {
+        let mut contract = series_contract(200, 2);
+        testing_env!(get_context(alice(), 50));
+        contract.dr_spawn_due(U64(0));
+        contract.dr_spawn_due(U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "series has no data requests left")]
+    fn series_spawn_after_last() {
+        let mut contract = series_contract(100, 1);
+        testing_env!(get_context(alice(), 50));
+        contract.dr_spawn_due(U64(0));
+        testing_env!(get_context(alice(), 150));
+        contract.dr_spawn_due(U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Series escrow of 200 not reached, received only 199")]
+    fn series_underfunded() {
+        series_contract(199, 2);
+    }
+
+    #[test]
+    fn series_cancel() {
+        let mut contract = series_contract(200, 2);
+        testing_env!(get_context(bob(), 0));
+        contract.dr_cancel_series(U64(0));
+        let series = contract.get_series(U64(0)).unwrap();
+        assert_eq!(series.escrow, U128(0));
+        assert_eq!(series.remaining, 0);
+    }
+
+    #[test]
+    fn series_spawn_with_settings() {
+        testing_env!(get_context(bob(), 0));
+        let whitelist = Some(vec![registry_entry(bob())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config_for_quick_final_arb());
+        contract.storage_deposit(Some(to_valid(bob())));
+
+        let mut args = series_args(1);
+        args.template.settings.answer_format = Some(AnswerFormat::MultiSelect);
+        testing_env!(get_context(token(), 0));
+        contract.dr_new_series(bob(), 100, args);
+
+        testing_env!(get_context(alice(), 50));
+        contract.dr_spawn_due(U64(0));
+        assert_eq!(
+            contract.get_answer_format(U64(0)),
+            Some(AnswerFormat::MultiSelect)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "answer formats can only be set on string requests")]
+    fn series_invalid_settings() {
+        testing_env!(get_context(bob(), 0));
+        let whitelist = Some(vec![registry_entry(bob())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config_for_quick_final_arb());
+
+        let mut args = series_args(1);
+        args.template.outcomes = None;
+        args.template.data_type = DataRequestDataType::Number(U128(100));
+        args.template.settings.answer_format = Some(AnswerFormat::Boolean);
+        testing_env!(get_context(token(), 0));
+        contract.dr_new_series(bob(), 100, args);
+    }
+
+    #[test]
+    #[should_panic(expected = "Oracle is paused")]
+    fn series_cancel_paused() {
+        let mut contract = series_contract(200, 2);
+        testing_env!(get_context(gov(), 0));
+        contract.toggle_pause();
+        testing_env!(get_context(bob(), 0));
+        contract.dr_cancel_series(U64(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "This function can only be called by bob.near")]
+    fn series_cancel_not_requester() {
+        let mut contract = series_contract(200, 2);
+        testing_env!(get_context(alice(), 0));
+        contract.dr_cancel_series(U64(0));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::fungible_token::{fungible_token_transfer, GAS_BASE_RESOLVE_TRANSFER};
+use crate::*;
+use flux_sdk::{consts::GAS_BASE_TRANSFER, types::WrappedBalance};
+use near_sdk::{
+    ext_contract,
+    serde::{Deserialize, Serialize},
+    Promise,
+};
+
+/// Gas of the transfer and callback `payout` schedules
+pub const GAS_BASE_PAYOUT: Gas = GAS_BASE_TRANSFER + GAS_BASE_RESOLVE_TRANSFER;
+
+/// Tokens owed to an account of which the `ft_transfer` failed, e.g. because the account had no storage registered
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FailedPayout {
+    pub token: AccountId,
+    pub amount: WrappedBalance,
+}
+
+#[ext_contract(ext_self)]
+trait SelfExt {
+    fn dr_resolve_payout(account_id: AccountId, token: AccountId, amount: U128) -> bool;
+}
+
+impl Contract {
+    /**
+     * @notice Transfers `amount` of `token` to `receiver` and records the payout as failed if the transfer does not go through
+     */
+    pub fn payout(&self, token: AccountId, receiver: AccountId, amount: Balance) -> Promise {
+        fungible_token_transfer(token.to_string(), receiver.to_string(), amount).then(
+            ext_self::dr_resolve_payout(
+                receiver,
+                token,
+                U128(amount),
+                // NEAR params
+                &env::current_account_id(),
+                0,
+                GAS_BASE_RESOLVE_TRANSFER,
+            ),
+        )
+    }
+
+    pub fn record_failed_payout(
+        &mut self,
+        account_id: &AccountId,
+        token: AccountId,
+        amount: Balance,
+    ) {
+        let mut payouts = self.failed_payouts.get(account_id).unwrap_or_default();
+        let total = match payouts.iter_mut().find(|payout| payout.token == token) {
+            Some(payout) => {
+                payout.amount = U128(u128::from(payout.amount) + amount);
+                payout.amount.into()
+            }
+            None => {
+                payouts.push(FailedPayout {
+                    token: token.to_string(),
+                    amount: U128(amount),
+                });
+                amount
+            }
+        };
+        self.failed_payouts.insert(account_id, &payouts);
+        logger::log_failed_payout(account_id, &token, total);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * @notice Re-credits a payout to `account_id` if its `ft_transfer` failed
+     * @returns whether the payout was transferred
+     */
+    #[private]
+    pub fn dr_resolve_payout(
+        &mut self,
+        account_id: AccountId,
+        token: AccountId,
+        amount: U128,
+    ) -> bool {
+        if helpers::is_promise_success() {
+            return true;
+        }
+
+        self.record_failed_payout(&account_id, token, amount.into());
+
+        false
+    }
+
+    /**
+     * @notice Retries the transfers of all payouts that failed for the predecessor
+     */
+    pub fn dr_withdraw_failed_payouts(&mut self) -> Promise {
+        self.assert_unpaused();
+        let account_id = env::predecessor_account_id();
+        let payouts = self
+            .failed_payouts
+            .remove(&account_id)
+            .expect("No failed payouts to withdraw");
+
+        assert!(
+            env::prepaid_gas() - env::used_gas() >= payouts.len() as u64 * GAS_BASE_PAYOUT,
+            "not enough gas to retry all payouts"
+        );
+
+        for payout in payouts.iter() {
+            logger::log_failed_payout(&account_id, &payout.token, 0);
+        }
+
+        payouts
+            .into_iter()
+            .map(|payout| self.payout(payout.token, account_id.to_string(), payout.amount.into()))
+            .reduce(|promise, next| promise.and(next))
+            .unwrap()
+    }
+
+    pub fn get_failed_payouts(&self, account_id: AccountId) -> Vec<FailedPayout> {
+        self.failed_payouts.get(&account_id).unwrap_or_default()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use crate::test_utils::*;
+    use flux_sdk::config::OracleConfig;
+    use near_sdk::{testing_env, MockedBlockchain, PromiseResult, VMContext};
+
+    fn oracle() -> AccountId {
+        "oracle.near".to_string()
+    }
+
+    fn stake_token() -> AccountId {
+        "stake.near".to_string()
+    }
+
+    fn config() -> OracleConfig {
+        OracleConfig {
+            stake_token: stake_token(),
+            ..zero_fee_config_for_quick_final_arb()
+        }
+    }
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContext {
+        VMContext {
+            current_account_id: oracle(),
+            account_balance: 1000 * 10u128.pow(24),
+            attached_deposit: 0,
+            ..crate::test_utils::get_context(predecessor_account_id)
+        }
+    }
+
+    fn resolve_payout(
+        contract: &mut Contract,
+        result: PromiseResult,
+        token: AccountId,
+        amount: u128,
+    ) -> bool {
+        testing_env!(
+            get_context(oracle()),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.dr_resolve_payout(alice(), token, U128(amount))
+    }
+
+    #[test]
+    fn payout_success_not_recorded() {
+        testing_env!(get_context(oracle()));
+        let mut contract = Contract::new(None, config());
+
+        assert!(resolve_payout(
+            &mut contract,
+            PromiseResult::Successful(vec![]),
+            token(),
+            10
+        ));
+        assert!(contract.get_failed_payouts(alice()).is_empty());
+    }
+
+    #[test]
+    fn payout_failed_recorded_per_token() {
+        testing_env!(get_context(oracle()));
+        let mut contract = Contract::new(None, config());
+
+        assert!(!resolve_payout(
+            &mut contract,
+            PromiseResult::Failed,
+            token(),
+            10
+        ));
+        assert!(!resolve_payout(
+            &mut contract,
+            PromiseResult::Failed,
+            token(),
+            5
+        ));
+        assert!(!resolve_payout(
+            &mut contract,
+            PromiseResult::Failed,
+            stake_token(),
+            7
+        ));
+
+        let payouts = contract.get_failed_payouts(alice());
+        assert_eq!(payouts.len(), 2);
+        assert_eq!(payouts[0].token, token());
+        assert_eq!(payouts[0].amount, U128(15));
+        assert_eq!(payouts[1].token, stake_token());
+        assert_eq!(payouts[1].amount, U128(7));
+    }
+
+    #[test]
+    fn withdraw_failed_payouts() {
+        testing_env!(get_context(oracle()));
+        let mut contract = Contract::new(None, config());
+        resolve_payout(&mut contract, PromiseResult::Failed, token(), 10);
+        resolve_payout(&mut contract, PromiseResult::Failed, stake_token(), 7);
+
+        testing_env!(get_context(alice()));
+        contract.dr_withdraw_failed_payouts();
+        assert!(contract.get_failed_payouts(alice()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "No failed payouts to withdraw")]
+    fn withdraw_no_failed_payouts() {
+        testing_env!(get_context(oracle()));
+        let mut contract = Contract::new(None, config());
+
+        testing_env!(get_context(alice()));
+        contract.dr_withdraw_failed_payouts();
+    }
+}
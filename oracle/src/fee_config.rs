@@ -1,8 +1,19 @@
 use crate::*;
-use flux_sdk::{config::FeeConfig, consts::MAX_RESOLUTION_FEE_PERCENTAGE};
+use flux_sdk::{
+    config::{FeeConfig, OracleConfig},
+    consts::MAX_RESOLUTION_FEE_PERCENTAGE,
+};
+
+/// Denominator of `FeeConfig.resolution_fee_percentage`, e.g. 5000 == 5%
+pub const RESOLUTION_FEE_DIVISOR: u128 = 100_000;
 
 #[near_bindgen]
 impl Contract {
+    // @returns the fee, on top of the validity bond, a new `DataRequest` currently has to pay
+    pub fn get_resolution_fee(&self) -> U128 {
+        U128(calc_resolution_fee(&self.get_config()))
+    }
+
     // @notice sets FLUX market cap, TVS, and fee percentage by updating current oracle config
     // replaces the `fee` field inside oracle config with updated FeeConfig
     pub fn update_fee_config(&mut self, new_fee_config: FeeConfig) {
@@ -31,69 +42,46 @@ impl Contract {
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-#[cfg(test)]
-mod mock_token_basic_tests {
-    use super::*;
-    use near_sdk::{
-        json_types::{U128, U64},
-        testing_env, MockedBlockchain, VMContext,
-    };
-
-    fn alice() -> AccountId {
-        "alice.near".to_string()
+/**
+ * @notice Calculates the resolution fee for a new `DataRequest` from the oracle's `FeeConfig`
+ * The fee is `resolution_fee_percentage` of the validity bond, scaled by the share of the FLUX market cap that is
+ * not staked yet. The lower the total value staked (TVS) is compared to the market cap the higher the fee, which
+ * attracts new stake when the oracle's security is low
+ * @returns The resolution fee denominated in `payment_token`
+ */
+pub fn calc_resolution_fee(config: &OracleConfig) -> Balance {
+    let flux_market_cap: u128 = config.fee.flux_market_cap.into();
+    let total_value_staked: u128 = config.fee.total_value_staked.into();
+    if flux_market_cap == 0 || total_value_staked >= flux_market_cap {
+        return 0;
     }
 
-    fn bob() -> AccountId {
-        "bob.near".to_string()
-    }
+    let max_fee = helpers::calc_product(
+        config.validity_bond.into(),
+        config.fee.resolution_fee_percentage.into(),
+        RESOLUTION_FEE_DIVISOR,
+    );
 
-    fn token() -> AccountId {
-        "token.near".to_string()
-    }
+    helpers::calc_product(
+        max_fee,
+        flux_market_cap - total_value_staked,
+        flux_market_cap,
+    )
+}
 
-    fn gov() -> AccountId {
-        "gov.near".to_string()
-    }
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use crate::test_utils::*;
+    use near_sdk::{json_types::U128, testing_env, MockedBlockchain};
 
     fn config(gov: AccountId) -> OracleConfig {
         OracleConfig {
             gov,
-            final_arbitrator: alice(),
-            payment_token: token(),
-            stake_token: token(),
             validity_bond: U128(0),
-            max_outcomes: 8,
-            default_challenge_window_duration: U64(1000),
-            min_initial_challenge_window_duration: U64(1000),
-            final_arbitrator_invoke_amount: U128(25_000_000_000_000_000_000_000_000_000_000),
-            fee: FeeConfig {
-                flux_market_cap: U128(50000),
-                total_value_staked: U128(10000),
-                resolution_fee_percentage: 5000, // 5%
-            },
-            min_resolution_bond: U128(100),
-        }
-    }
-
-    fn get_context(predecessor_account_id: AccountId) -> VMContext {
-        VMContext {
-            current_account_id: token(),
-            signer_account_id: bob(),
-            signer_account_pk: vec![0, 1, 2],
-            predecessor_account_id,
-            input: vec![],
-            block_index: 0,
-            block_timestamp: 0,
-            account_balance: 1000 * 10u128.pow(24),
-            account_locked_balance: 0,
-            storage_usage: 10u64.pow(6),
-            attached_deposit: 15600000000000000000000,
-            prepaid_gas: 10u64.pow(18),
-            random_seed: vec![0, 1, 2],
-            is_view: false,
-            output_data_receivers: vec![],
-            epoch_height: 0,
+            fee: fee_config(5000), // 5%
+            ..new_config(25_000_000_000_000_000_000_000_000_000_000)
         }
     }
 
@@ -122,4 +110,29 @@ mod mock_token_basic_tests {
         };
         contract.update_fee_config(new_fee_config);
     }
+
+    #[test]
+    fn g_resolution_fee() {
+        testing_env!(get_context(gov()));
+        let mut config = config(gov());
+        config.validity_bond = U128(1000);
+        let contract = Contract::new(None, config);
+        // 1000 * 5% * (50000 - 10000) / 50000
+        assert_eq!(contract.get_resolution_fee(), U128(40));
+    }
+
+    #[test]
+    fn g_resolution_fee_updates() {
+        testing_env!(get_context(gov()));
+        let mut config = config(gov());
+        config.validity_bond = U128(1000);
+        let mut contract = Contract::new(None, config);
+        contract.update_fee_config(FeeConfig {
+            flux_market_cap: U128(50000),
+            total_value_staked: U128(40000),
+            resolution_fee_percentage: 10000, // 10%
+        });
+        // 1000 * 10% * (50000 - 40000) / 50000
+        assert_eq!(contract.get_resolution_fee(), U128(20));
+    }
 }
@@ -0,0 +1,53 @@
+use crate::*;
+use near_sdk::{
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TopUpDataRequestArgs {
+    pub id: U64,
+}
+
+impl Contract {
+    // @returns map of account id to the fee it added to data request `dr_id`
+    fn top_ups_of(&self, dr_id: u64) -> LookupMap<AccountId, Balance> {
+        self.fee_top_up_senders
+            .get(&dr_id)
+            .unwrap_or_else(|| LookupMap::new(format!("fts:{}:", dr_id).as_bytes().to_vec()))
+    }
+
+    // @returns the fee added to data request `dr_id` through top-ups
+    pub fn fee_top_up_total(&self, dr_id: u64) -> Balance {
+        self.fee_top_ups.get(&dr_id).unwrap_or(0)
+    }
+
+    // @notice records that `sender` added `amount` to the fee of data request `dr_id`
+    pub fn add_fee_top_up(&mut self, dr_id: u64, sender: &AccountId, amount: Balance) {
+        let total = self.fee_top_up_total(dr_id) + amount;
+        self.fee_top_ups.insert(&dr_id, &total);
+
+        let mut top_ups = self.top_ups_of(dr_id);
+        let sent = top_ups.get(sender).unwrap_or(0);
+        top_ups.insert(sender, &(sent + amount));
+        self.fee_top_up_senders.insert(&dr_id, &top_ups);
+    }
+
+    /**
+     * @notice Removes the top-ups `account_id` sent to data request `dr_id`
+     * @returns the fee `account_id` added, `None` if it added nothing or already got it back
+     */
+    pub fn take_fee_top_up(&mut self, dr_id: u64, account_id: &AccountId) -> Option<Balance> {
+        self.top_ups_of(dr_id).remove(account_id)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // @returns the fee `account_id` added to data request `request_id` that it has not gotten back
+    pub fn get_fee_top_up(&self, request_id: U64, account_id: AccountId) -> U128 {
+        let top_ups = self.top_ups_of(request_id.into());
+        U128(top_ups.get(&account_id).unwrap_or(0))
+    }
+}
@@ -1,7 +1,10 @@
-use near_sdk::{ext_contract, json_types::U128, AccountId, Promise};
+use near_sdk::{ext_contract, json_types::U128, AccountId, Gas, Promise};
 
 use flux_sdk::consts::GAS_BASE_TRANSFER;
 
+/// Gas reserved for the callback that handles the result of a `ft_transfer`
+pub const GAS_BASE_RESOLVE_TRANSFER: Gas = 10_000_000_000_000;
+
 #[ext_contract]
 pub trait FungibleToken {
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
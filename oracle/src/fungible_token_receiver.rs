@@ -1,3 +1,7 @@
+use crate::commit_reveal::CommitStakeArgs;
+use crate::data_request::DataRequestSettings;
+use crate::data_request_series::NewDataRequestSeriesArgs;
+use crate::fee_top_up::TopUpDataRequestArgs;
 use crate::*;
 use flux_sdk::{
     data_request::{NewDataRequestArgs, StakeDataRequestArgs},
@@ -12,10 +16,24 @@ use near_sdk::{
     serde_json, PromiseOrValue,
 };
 
+/// `NewDataRequestArgs` with the optional settings of the request next to its other fields
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NewDataRequestPayload {
+    #[serde(flatten)]
+    pub args: NewDataRequestArgs,
+    #[serde(flatten)]
+    pub settings: DataRequestSettings,
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum Payload {
-    NewDataRequest(NewDataRequestArgs),
+    NewDataRequest(NewDataRequestPayload),
     StakeDataRequest(StakeDataRequestArgs),
+    NewDataRequestSeries(NewDataRequestSeriesArgs),
+    TopUpDataRequest(TopUpDataRequestArgs),
+    CommitStake(CommitStakeArgs),
+    ProviderProposal(StakeDataRequestArgs),
 }
 
 #[near_bindgen]
@@ -42,8 +60,13 @@ impl FungibleTokenReceiver for Contract {
                     env::predecessor_account_id(),
                     "ERR_WRONG_PAYMENT_TOKEN"
                 );
-                self.ft_dr_new_callback(sender.clone(), amount.into(), payload)
-                    .into()
+                self.ft_dr_new_callback(
+                    sender.clone(),
+                    amount.into(),
+                    payload.args,
+                    payload.settings,
+                )
+                .into()
             }
             Payload::StakeDataRequest(payload) => {
                 assert_eq!(
@@ -53,6 +76,27 @@ impl FungibleTokenReceiver for Contract {
                 );
                 self.dr_stake(sender.clone(), amount.into(), payload)
             }
+            Payload::NewDataRequestSeries(payload) => {
+                assert_eq!(
+                    config.payment_token,
+                    env::predecessor_account_id(),
+                    "ERR_WRONG_PAYMENT_TOKEN"
+                );
+                PromiseOrValue::Value(U128(self.dr_new_series(
+                    sender.clone(),
+                    amount.into(),
+                    payload,
+                )))
+            }
+            Payload::TopUpDataRequest(payload) => {
+                self.dr_top_up(sender.clone(), amount.into(), payload)
+            }
+            Payload::CommitStake(payload) => {
+                self.dr_commit_stake(sender.clone(), amount.into(), payload)
+            }
+            Payload::ProviderProposal(payload) => {
+                self.dr_propose_by_provider(sender.clone(), amount.into(), payload)
+            }
         };
 
         self.use_storage(&sender, initial_storage_usage, account.available);
@@ -66,8 +110,9 @@ impl FungibleTokenReceiver for Contract {
 mod mock_token_basic_tests {
     use super::*;
     use crate::storage_manager::StorageManager;
+    use crate::test_utils::*;
     use flux_sdk::{
-        config::{FeeConfig, OracleConfig},
+        config::OracleConfig,
         data_request::{DataRequestDataType, NewDataRequestArgs},
         outcome::{AnswerType, Outcome},
     };
@@ -77,83 +122,21 @@ mod mock_token_basic_tests {
     };
     use std::convert::TryInto;
 
-    fn alice() -> AccountId {
-        "alice.near".to_string()
-    }
-
-    fn bob() -> AccountId {
-        "bob.near".to_string()
-    }
-
-    fn carol() -> AccountId {
-        "carol.near".to_string()
-    }
-
-    fn token() -> AccountId {
-        "token.near".to_string()
-    }
-
-    fn gov() -> AccountId {
-        "gov.near".to_string()
-    }
-
     fn to_valid(account: AccountId) -> ValidAccountId {
         account.try_into().expect("invalid account")
     }
 
-    fn registry_entry(account: AccountId) -> Requester {
-        Requester {
-            contract_name: account.clone(),
-            account_id: account.clone(),
-            stake_multiplier: None,
-            code_base_url: None,
-        }
-    }
-
     fn config() -> OracleConfig {
         OracleConfig {
-            gov: gov(),
-            final_arbitrator: alice(),
-            payment_token: token(),
-            stake_token: token(),
             validity_bond: U128(0),
-            max_outcomes: 8,
-            default_challenge_window_duration: U64(1000),
-            min_initial_challenge_window_duration: U64(1000),
-            final_arbitrator_invoke_amount: U128(250),
-            fee: FeeConfig {
-                flux_market_cap: U128(50000),
-                total_value_staked: U128(10000),
-                resolution_fee_percentage: 5000, // 5%
-            },
-            min_resolution_bond: U128(100),
-        }
-    }
-
-    fn get_context(predecessor_account_id: AccountId) -> VMContext {
-        VMContext {
-            current_account_id: token(),
-            signer_account_id: bob(),
-            signer_account_pk: vec![0, 1, 2],
-            predecessor_account_id,
-            input: vec![],
-            block_index: 0,
-            block_timestamp: 0,
-            account_balance: 1000 * 10u128.pow(24),
-            account_locked_balance: 0,
-            storage_usage: 10u64.pow(6),
-            attached_deposit: 1000 * 10u128.pow(24),
-            prepaid_gas: 10u64.pow(18),
-            random_seed: vec![0, 1, 2],
-            is_view: false,
-            output_data_receivers: vec![],
-            epoch_height: 0,
+            fee: fee_config(5000), // 5%
+            ..new_config(250)
         }
     }
 
     #[test]
     #[should_panic(
-        expected = "alice.near has 0 deposited, 3540000000000000000000 is required for this transaction"
+        expected = "alice.near has 0 deposited, 8090000000000000000000 is required for this transaction"
     )]
     fn transfer_storage_no_funds() {
         testing_env!(get_context(token()));
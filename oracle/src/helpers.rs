@@ -1,4 +1,4 @@
-use near_sdk::{env, AccountId, Balance, Promise, StorageUsage};
+use near_sdk::{env, AccountId, Balance, Promise, PromiseResult, StorageUsage};
 use uint::construct_uint;
 
 construct_uint! {
@@ -37,6 +37,14 @@ pub fn refund_storage(initial_storage: StorageUsage, sender_id: AccountId) {
     }
 }
 
+// @returns the lowercase hex encoded sha256 digest of `data`
+pub fn sha256_hex(data: &[u8]) -> String {
+    env::sha256(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
 pub fn ns_to_ms(ns_timestamp: u64) -> u64 {
     ns_timestamp / 1_000_000
 }
@@ -50,3 +58,13 @@ pub fn multiply_stake(stake: u128, multiplier: Option<u16>) -> u128 {
         None => stake,
     }
 }
+
+// @returns whether the promise this callback was chained to succeeded
+pub fn is_promise_success() -> bool {
+    assert_eq!(
+        env::promise_results_count(),
+        1,
+        "Contract expected a result on the callback"
+    );
+    matches!(env::promise_result(0), PromiseResult::Successful(_))
+}
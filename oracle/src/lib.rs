@@ -2,23 +2,35 @@
 
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::{LookupMap, Vector},
+    collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet, Vector},
     env,
     json_types::U128,
-    near_bindgen, AccountId, Balance,
+    near_bindgen, AccountId, Balance, Gas,
 };
 
 near_sdk::setup_alloc!();
 
+pub mod account_stakes;
+pub mod answer_format;
+pub mod bond_settings;
 pub mod callback_args;
+pub mod commit_reveal;
 pub mod data_request;
+pub mod data_request_series;
+pub mod failed_payouts;
 pub mod fee_config;
+pub mod fee_top_up;
 mod fungible_token_receiver;
 mod helpers;
 mod logger;
+pub mod numeric_bounds;
 pub mod oracle_config;
+pub mod outcome_delivery;
+pub mod payload_reveal;
+pub mod provider_quorum;
 mod requester_handler;
 mod resolution_window;
+mod stake_totals;
 mod storage_manager;
 mod upgrade;
 pub mod whitelist;
@@ -26,11 +38,23 @@ pub mod whitelist;
 /// Mocks
 mod fungible_token;
 
+/// Test fixtures
+#[cfg(test)]
+mod test_utils;
+
 pub use callback_args::*;
 
+use account_stakes::AccountStake;
+use answer_format::AnswerFormat;
+use bond_settings::BondSettings;
+use commit_reveal::CommitRound;
+use failed_payouts::FailedPayout;
 use flux_sdk::{
     config::OracleConfig, config::OracleStorageKey, data_request::DataRequest, requester::Requester,
 };
+use numeric_bounds::{NumericBounds, RoundNumbers};
+use outcome_delivery::OutcomeDelivery;
+use provider_quorum::ProviderQuorum;
 use storage_manager::AccountStorageBalance;
 
 #[near_bindgen]
@@ -41,6 +65,25 @@ pub struct Contract {
     pub data_requests: Vector<DataRequest>,
     pub accounts: LookupMap<AccountId, AccountStorageBalance>, // storage map
     pub paused: bool,
+    pub failed_payouts: LookupMap<AccountId, Vec<FailedPayout>>,
+    pub extended_config: oracle_config::ExtendedOracleConfig,
+    pub set_outcome_gas: LookupMap<AccountId, Gas>, // gas requesters declared for their `set_outcome`
+    pub deliveries: LookupMap<u64, OutcomeDelivery>,
+    pub failed_deliveries: UnorderedSet<u64>,
+    pub series: Vector<data_request_series::DataRequestSeries>,
+    pub cancelled_requests: LookupSet<u64>,
+    pub request_expiries: LookupMap<u64, u64>, // time from which a data request nobody staked on can be finalized
+    pub account_stakes: LookupMap<AccountId, UnorderedMap<u64, Vec<AccountStake>>>, // stakes of an account per data request
+    pub total_bonded: LookupMap<u64, Balance>, // total bond size of the bonded rounds of a data request
+    pub fee_top_ups: LookupMap<u64, Balance>, // fee added to a data request on top of its resolution fee
+    pub fee_top_up_senders: LookupMap<u64, LookupMap<AccountId, Balance>>, // top-ups of a data request per sender
+    pub bond_settings: LookupMap<u64, BondSettings>,
+    pub numeric_bounds: LookupMap<u64, NumericBounds>,
+    pub round_numbers: LookupMap<u64, RoundNumbers>, // numbers staked in the open round of a data request with a tolerance
+    pub answer_formats: LookupMap<u64, AnswerFormat>,
+    pub revealed_payloads: LookupMap<u64, String>, // payload revealed for the digest a data request finalized on
+    pub commit_rounds: LookupMap<u64, CommitRound>, // commits on the first round of a data request
+    pub provider_quorums: LookupMap<u64, ProviderQuorum>,
 }
 
 impl Default for Contract {
@@ -63,6 +106,25 @@ impl Contract {
             data_requests: Vector::new(OracleStorageKey::DataRequests),
             accounts: LookupMap::new(OracleStorageKey::Accounts),
             paused: false,
+            failed_payouts: LookupMap::new(b"fp".to_vec()),
+            extended_config: oracle_config::ExtendedOracleConfig::default(),
+            set_outcome_gas: LookupMap::new(b"sog".to_vec()),
+            deliveries: LookupMap::new(b"dl".to_vec()),
+            failed_deliveries: UnorderedSet::new(b"fd".to_vec()),
+            series: Vector::new(b"srs".to_vec()),
+            cancelled_requests: LookupSet::new(b"cdr".to_vec()),
+            request_expiries: LookupMap::new(b"exp".to_vec()),
+            account_stakes: LookupMap::new(b"as".to_vec()),
+            total_bonded: LookupMap::new(b"tb".to_vec()),
+            fee_top_ups: LookupMap::new(b"fto".to_vec()),
+            fee_top_up_senders: LookupMap::new(b"fts".to_vec()),
+            bond_settings: LookupMap::new(b"bs".to_vec()),
+            numeric_bounds: LookupMap::new(b"nb".to_vec()),
+            round_numbers: LookupMap::new(b"rn".to_vec()),
+            answer_formats: LookupMap::new(b"af".to_vec()),
+            revealed_payloads: LookupMap::new(b"rvp".to_vec()),
+            commit_rounds: LookupMap::new(b"cmr".to_vec()),
+            provider_quorums: LookupMap::new(b"pq".to_vec()),
         }
     }
 }
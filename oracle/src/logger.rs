@@ -1,4 +1,8 @@
-use crate::helpers::ns_to_ms;
+use crate::{
+    answer_format::AnswerFormat, commit_reveal::CommitRound, data_request_series::DataRequestSeries,
+    helpers::ns_to_ms, numeric_bounds::NumericBounds, oracle_config::ExtendedOracleConfig,
+    outcome_delivery::OutcomeDelivery, provider_quorum::ProviderQuorum,
+};
 use flux_sdk::{
     config::OracleConfig,
     data_request::ActiveDataRequest,
@@ -11,10 +15,16 @@ use near_sdk::{
     env,
     json_types::{U128, U64},
     serde_json::json,
-    AccountId, Balance,
+    AccountId, Balance, Gas,
 };
 
-pub fn log_new_data_request(request: &ActiveDataRequest) {
+pub fn log_new_data_request(
+    request: &ActiveDataRequest,
+    resolution_fee: Balance,
+    numeric_bounds: Option<NumericBounds>,
+    answer_format: Option<AnswerFormat>,
+    provider_quorum: Option<ProviderQuorum>,
+) {
     env::log(
         json!({
             "type": "data_requests",
@@ -31,12 +41,16 @@ pub fn log_new_data_request(request: &ActiveDataRequest) {
                 "initial_challenge_period": U64(request.initial_challenge_period),
                 "final_arbitrator_triggered": request.final_arbitrator_triggered,
                 "paid_fee": U128(request.request_config.paid_fee),
+                "resolution_fee": U128(resolution_fee),
                 "stake_multiplier": request.request_config.stake_multiplier,
                 "global_config_id": U64(request.global_config_id),
                 "tags": request.tags,
                 "date": U64(ns_to_ms(env::block_timestamp())),
                 "block_height": U64(env::block_index()),
                 "data_type": request.data_type,
+                "numeric_bounds": numeric_bounds,
+                "answer_format": answer_format,
+                "provider_quorum": provider_quorum,
             }
         })
         .to_string()
@@ -64,6 +78,64 @@ pub fn log_update_active_data_request(request: &ActiveDataRequest) {
     );
 }
 
+pub fn log_fee_top_up(request: &ActiveDataRequest, sender: &AccountId, amount: Balance) {
+    env::log(
+        json!({
+            "type": "data_requests",
+            "action": "update",
+            "cap_id": format!("dr_{}", request.id),
+            "params": {
+                "id": U64(request.id),
+                "paid_fee": U128(request.request_config.paid_fee),
+                "fee_top_up": U128(amount),
+                "fee_top_up_sender": sender,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
+pub fn log_fee_top_up_refund(request_id: u64, sender: &AccountId, amount: Balance) {
+    env::log(
+        json!({
+            "type": "data_requests",
+            "action": "update",
+            "cap_id": format!("dr_{}", request_id),
+            "params": {
+                "id": U64(request_id),
+                "fee_top_up_refund": U128(amount),
+                "fee_top_up_sender": sender,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
+pub fn log_cancel_data_request(request: &ActiveDataRequest, refund: Balance) {
+    env::log(
+        json!({
+            "type": "data_requests",
+            "action": "update",
+            "cap_id": format!("dr_{}", request.id),
+            "params": {
+                "id": U64(request.id),
+                "cancelled": true,
+                "refund": U128(refund),
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
 pub fn log_update_finalized_data_request(request: &FinalizedDataRequest) {
     env::log(
         json!({
@@ -80,6 +152,29 @@ pub fn log_update_finalized_data_request(request: &FinalizedDataRequest) {
     );
 }
 
+pub fn log_data_request_series(series: &DataRequestSeries) {
+    env::log(
+        json!({
+            "type": "data_request_series",
+            "action": "update",
+            "cap_id": format!("drs_{}", u64::from(series.id)),
+            "params": {
+                "id": series.id,
+                "requester": series.requester,
+                "tags": series.template.tags,
+                "interval": series.interval,
+                "next_at": series.next_at,
+                "remaining": series.remaining,
+                "escrow": series.escrow,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
 pub fn log_oracle_config(config: &OracleConfig, id: u64) {
     env::log(
         json!({
@@ -112,6 +207,135 @@ pub fn log_oracle_config(config: &OracleConfig, id: u64) {
     );
 }
 
+pub fn log_extended_oracle_config(config: &ExtendedOracleConfig) {
+    env::log(
+        json!({
+            "type": "extended_oracle_config",
+            "action": "update",
+            "cap_id": "eoc",
+            "params": {
+                "id": "eoc",
+                "max_set_outcome_gas": config.max_set_outcome_gas,
+                "request_expiry": config.request_expiry,
+                "bond_limits": config.bond_limits,
+                "slash_unrevealed_commits": config.slash_unrevealed_commits,
+                "min_commit_share": config.min_commit_share,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
+pub fn log_outcome_delivery(request_id: u64, delivery: &OutcomeDelivery) {
+    env::log(
+        json!({
+            "type": "outcome_deliveries",
+            "action": "update",
+            "cap_id": format!("od_{}", request_id),
+            "params": {
+                "id": format!("od_{}", request_id),
+                "data_request_id": U64(request_id),
+                "requester": delivery.requester.account_id,
+                "status": delivery.status,
+                "finalized_at": delivery.finalized_at,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
+pub fn log_commit_round(request_id: u64, round: &CommitRound) {
+    env::log(
+        json!({
+            "type": "commit_rounds",
+            "action": "update",
+            "cap_id": format!("cmr_{}", request_id),
+            "params": {
+                "id": format!("cmr_{}", request_id),
+                "data_request_id": U64(request_id),
+                "commit_end": round.commit_end,
+                "reveal_end": round.reveal_end,
+                "commits": round.commits,
+                "settled": round.settled,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
+pub fn log_provider_quorum(request_id: u64, quorum: &ProviderQuorum) {
+    env::log(
+        json!({
+            "type": "provider_quorums",
+            "action": "update",
+            "cap_id": format!("pq_{}", request_id),
+            "params": {
+                "id": format!("pq_{}", request_id),
+                "data_request_id": U64(request_id),
+                "providers": quorum.providers,
+                "threshold": quorum.threshold,
+                "submission_end": quorum.submission_end,
+                "challenge_duration": quorum.challenge_duration,
+                "submissions": quorum.submissions,
+                "agreed_outcome": quorum.agreed_outcome,
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
+// the payload itself is left out as it can exceed the log size limit, it can be read from the reveal's arguments
+pub fn log_payload_reveal(request_id: u64, payload: &str) {
+    env::log(
+        json!({
+            "type": "payload_reveals",
+            "action": "update",
+            "cap_id": format!("pr_{}", request_id),
+            "params": {
+                "id": format!("pr_{}", request_id),
+                "data_request_id": U64(request_id),
+                "payload_length": U64(payload.len() as u64),
+                "revealer": env::predecessor_account_id(),
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
+pub fn log_set_outcome_gas(requester: &AccountId, gas: Gas) {
+    env::log(
+        json!({
+            "type": "set_outcome_gas",
+            "action": "update",
+            "cap_id": format!("sog_{}", requester),
+            "params": {
+                "id": format!("sog_{}", requester),
+                "requester": requester,
+                "gas": U64(gas),
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
 pub fn log_resolution_window(window: &ResolutionWindow) {
     env::log(
         json!({
@@ -252,6 +476,26 @@ pub fn log_claim(
     );
 }
 
+pub fn log_failed_payout(account_id: &AccountId, token: &AccountId, amount: Balance) {
+    env::log(
+        json!({
+            "type": "failed_payouts",
+            "action": "update",
+            "cap_id": format!("fp_{}_{}", account_id, token),
+            "params": {
+                "id": format!("fp_{}_{}", account_id, token),
+                "account_id": account_id,
+                "token": token,
+                "amount": U128(amount),
+                "date": U64(ns_to_ms(env::block_timestamp())),
+                "block_height": U64(env::block_index()),
+            }
+        })
+        .to_string()
+        .as_bytes(),
+    );
+}
+
 pub fn log_whitelist(requester: &Requester, active: bool) {
     env::log(
         json!({
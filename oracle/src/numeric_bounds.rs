@@ -0,0 +1,333 @@
+use crate::*;
+use flux_sdk::{
+    data_request::DataRequestDataType,
+    outcome::{AnswerNumberType, AnswerType, Outcome},
+};
+use near_sdk::{
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+};
+use std::cmp::Ordering;
+
+/// Signed number in the same scale as the answers of the data request, i.e. already multiplied by its multiplier
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignedNumber {
+    pub value: U128,
+    pub negative: bool,
+}
+
+impl SignedNumber {
+    fn compare(&self, other: &SignedNumber) -> Ordering {
+        let (a, b): (u128, u128) = (self.value.into(), other.value.into());
+        // -0 and 0 are the same number
+        match (self.negative && a > 0, other.negative && b > 0) {
+            (false, false) => a.cmp(&b),
+            (true, true) => b.cmp(&a),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+        }
+    }
+
+    // @returns the absolute difference between two numbers, `None` if it doesn't fit in a u128
+    fn distance(&self, other: &SignedNumber) -> Option<u128> {
+        let (a, b): (u128, u128) = (self.value.into(), other.value.into());
+        if self.negative == other.negative || a == 0 || b == 0 {
+            Some(if a > b { a - b } else { b - a })
+        } else {
+            a.checked_add(b)
+        }
+    }
+}
+
+impl From<&AnswerNumberType> for SignedNumber {
+    fn from(answer: &AnswerNumberType) -> Self {
+        Self {
+            value: answer.value,
+            negative: answer.negative,
+        }
+    }
+}
+
+/// Distinct numbers staked in the open round of a data request with a tolerance, in the order they were first staked
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RoundNumbers {
+    pub round: u16,
+    pub outcomes: Vec<Outcome>,
+}
+
+/// Limits on the answers of a numeric data request, any limit left out is not enforced
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NumericBounds {
+    pub min: Option<SignedNumber>,
+    pub max: Option<SignedNumber>,
+    // answers have to be a multiple of `step`, e.g. a step equal to the multiplier only allows whole numbers
+    pub step: Option<U128>,
+    // answers at most `tolerance` apart count as the same answer
+    pub tolerance: Option<U128>,
+}
+
+impl NumericBounds {
+    pub fn assert_valid(&self, data_type: &DataRequestDataType) {
+        match data_type {
+            DataRequestDataType::Number(_) => (),
+            DataRequestDataType::String => {
+                panic!("numeric bounds can only be set on number requests")
+            }
+        }
+        if let (Some(min), Some(max)) = (&self.min, &self.max) {
+            assert!(
+                min.compare(max) != Ordering::Greater,
+                "min can't be higher than max"
+            );
+        }
+        if let Some(step) = self.step {
+            assert!(u128::from(step) > 0, "step has to be higher than 0");
+        }
+    }
+
+    pub fn assert_within(&self, answer: &AnswerNumberType) {
+        let number = SignedNumber::from(answer);
+        if let Some(min) = &self.min {
+            assert!(
+                number.compare(min) != Ordering::Less,
+                "ERR_OUTCOME_BELOW_MIN"
+            );
+        }
+        if let Some(max) = &self.max {
+            assert!(
+                number.compare(max) != Ordering::Greater,
+                "ERR_OUTCOME_ABOVE_MAX"
+            );
+        }
+        if let Some(step) = self.step {
+            assert_eq!(
+                u128::from(answer.value) % u128::from(step),
+                0,
+                "ERR_OUTCOME_NOT_A_STEP"
+            );
+        }
+    }
+
+    // @returns whether `a` and `b` count as the same answer, which for numbers means at most `tolerance` apart
+    pub fn is_match(&self, a: &Outcome, b: &Outcome) -> bool {
+        if a == b {
+            return true;
+        }
+        match (self.tolerance, a, b) {
+            (
+                Some(tolerance),
+                Outcome::Answer(AnswerType::Number(a)),
+                Outcome::Answer(AnswerType::Number(b)),
+            ) => {
+                a.multiplier == b.multiplier
+                    && SignedNumber::from(a)
+                        .distance(&SignedNumber::from(b))
+                        .map(|distance| distance <= tolerance.into())
+                        .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Contract {
+    /**
+     * @notice Matches a staked number against the outcomes bonded in earlier rounds of data request `dr_id`. A number
+     * within the request's tolerance of a bonded outcome is recorded as that bonded outcome, the one bonded first if
+     * there are several. The number a request finalizes on is therefore always the first one bonded among the answers
+     * considered equal to it, and a round can't be challenged with an answer equal to its bonded outcome
+     * @returns the outcome the stake is recorded on
+     */
+    pub fn match_bonded_outcome(
+        &self,
+        dr_id: u64,
+        outcome: Outcome,
+        mut bonded_outcomes: impl Iterator<Item = Outcome>,
+    ) -> Outcome {
+        match self.numeric_bounds.get(&dr_id) {
+            Some(bounds) if bounds.tolerance.is_some() => bonded_outcomes
+                .find(|bonded_outcome| bounds.is_match(bonded_outcome, &outcome))
+                .unwrap_or(outcome),
+            _ => outcome,
+        }
+    }
+
+    /**
+     * @notice Picks the outcome stake on `outcome` in round `round` of data request `dr_id` is recorded on. Like
+     * `match_bonded_outcome` a number within tolerance of a bonded outcome is recorded as that outcome, otherwise it is
+     * recorded as the first number staked in `round` within tolerance of it. Numbers staked for the first time in a
+     * round are remembered for the stakes after them, so answers within tolerance of each other fill the same bond
+     * @returns the outcome the stake is recorded on
+     */
+    pub fn stake_outcome(
+        &mut self,
+        dr_id: u64,
+        round: u16,
+        outcome: Outcome,
+        bonded_outcomes: impl Iterator<Item = Outcome>,
+    ) -> Outcome {
+        let bounds = match self.numeric_bounds.get(&dr_id) {
+            Some(bounds) if bounds.tolerance.is_some() => bounds,
+            _ => return outcome,
+        };
+        let outcome = self.match_bonded_outcome(dr_id, outcome, bonded_outcomes);
+        match &outcome {
+            Outcome::Answer(AnswerType::Number(_)) => (),
+            _ => return outcome,
+        };
+
+        let mut numbers = self
+            .round_numbers
+            .get(&dr_id)
+            .filter(|numbers| numbers.round == round)
+            .unwrap_or(RoundNumbers {
+                round,
+                outcomes: Vec::new(),
+            });
+        if let Some(staked) = numbers
+            .outcomes
+            .iter()
+            .find(|staked| bounds.is_match(staked, &outcome))
+        {
+            return staked.clone();
+        }
+
+        numbers.outcomes.push(outcome.clone());
+        self.round_numbers.insert(&dr_id, &numbers);
+        outcome
+    }
+
+    // @returns whether `a` and `b` count as the same answer on data request `dr_id`
+    pub fn outcomes_match(&self, dr_id: u64, a: &Outcome, b: &Outcome) -> bool {
+        match self.numeric_bounds.get(&dr_id) {
+            Some(bounds) => bounds.is_match(a, b),
+            None => a == b,
+        }
+    }
+
+    // panics if `outcome` is a number outside of the bounds of data request `dr_id`
+    pub fn assert_within_bounds(&self, dr_id: u64, outcome: &Outcome) {
+        if let Outcome::Answer(AnswerType::Number(answer)) = outcome {
+            if let Some(bounds) = self.numeric_bounds.get(&dr_id) {
+                bounds.assert_within(answer);
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_numeric_bounds(&self, request_id: U64) -> Option<NumericBounds> {
+        self.numeric_bounds.get(&request_id.into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+
+    fn number(value: u128, negative: bool) -> SignedNumber {
+        SignedNumber {
+            value: U128(value),
+            negative,
+        }
+    }
+
+    fn answer(value: u128, negative: bool) -> AnswerNumberType {
+        AnswerNumberType {
+            value: U128(value),
+            multiplier: U128(100),
+            negative,
+        }
+    }
+
+    fn bounds() -> NumericBounds {
+        NumericBounds {
+            min: Some(number(500, true)),
+            max: Some(number(1000, false)),
+            step: Some(U128(100)),
+            tolerance: None,
+        }
+    }
+
+    #[test]
+    fn signed_number_order() {
+        assert_eq!(number(1, true).compare(&number(0, false)), Ordering::Less);
+        assert_eq!(number(2, true).compare(&number(1, true)), Ordering::Less);
+        assert_eq!(number(0, true).compare(&number(0, false)), Ordering::Equal);
+        assert_eq!(
+            number(2, false).compare(&number(1, false)),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn signed_number_distance() {
+        assert_eq!(number(3, true).distance(&number(2, false)), Some(5));
+        assert_eq!(number(3, true).distance(&number(2, true)), Some(1));
+        assert_eq!(number(0, false).distance(&number(2, true)), Some(2));
+        assert_eq!(number(u128::MAX, true).distance(&number(1, false)), None);
+    }
+
+    #[test]
+    fn answers_within_tolerance_match() {
+        let bounds = NumericBounds {
+            tolerance: Some(U128(5)),
+            ..bounds()
+        };
+        let outcome =
+            |value, negative| Outcome::Answer(AnswerType::Number(answer(value, negative)));
+        assert!(bounds.is_match(&outcome(100, false), &outcome(105, false)));
+        assert!(bounds.is_match(&outcome(2, true), &outcome(3, false)));
+        assert!(!bounds.is_match(&outcome(100, false), &outcome(106, false)));
+        assert!(!bounds().is_match(&outcome(100, false), &outcome(101, false)));
+    }
+
+    #[test]
+    fn answer_within_bounds() {
+        bounds().assert_within(&answer(500, true));
+        bounds().assert_within(&answer(0, false));
+        bounds().assert_within(&answer(1000, false));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_OUTCOME_BELOW_MIN")]
+    fn answer_below_min() {
+        bounds().assert_within(&answer(600, true));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_OUTCOME_ABOVE_MAX")]
+    fn answer_above_max() {
+        bounds().assert_within(&answer(1100, false));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_OUTCOME_NOT_A_STEP")]
+    fn answer_not_a_step() {
+        bounds().assert_within(&answer(150, false));
+    }
+
+    #[test]
+    #[should_panic(expected = "min can't be higher than max")]
+    fn bounds_inverted() {
+        NumericBounds {
+            min: Some(number(1, false)),
+            max: Some(number(1, true)),
+            step: None,
+            tolerance: None,
+        }
+        .assert_valid(&DataRequestDataType::Number(U128(100)));
+    }
+
+    #[test]
+    #[should_panic(expected = "numeric bounds can only be set on number requests")]
+    fn bounds_on_string_request() {
+        bounds().assert_valid(&DataRequestDataType::String);
+    }
+}
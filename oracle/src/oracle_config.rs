@@ -1,5 +1,41 @@
+use crate::bond_settings::BondLimits;
 use crate::*;
-use flux_sdk::config::OracleConfig;
+use flux_sdk::{config::OracleConfig, consts::GAS_BASE_SET_OUTCOME};
+use near_sdk::{
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+};
+
+/// 30 days in ns
+pub const DEFAULT_REQUEST_EXPIRY: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+
+/// Oracle settings that are not part of the `OracleConfig` of flux-sdk
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtendedOracleConfig {
+    // maximum gas a requester can have attached to the `set_outcome` call it receives on finalization
+    pub max_set_outcome_gas: U64,
+    // time in ns after creation from which a data request nobody staked on can be finalized as Invalid
+    pub request_expiry: U64,
+    // bounds for the bond settings requesters can give new data requests
+    pub bond_limits: BondLimits,
+    // whether stake commits that are not revealed go to gov instead of being refunded
+    pub slash_unrevealed_commits: bool,
+    // minimum stake commit as a share of the first round bond, divided by `MIN_COMMIT_SHARE_DIVISOR`
+    pub min_commit_share: u32,
+}
+
+impl Default for ExtendedOracleConfig {
+    fn default() -> Self {
+        Self {
+            max_set_outcome_gas: U64(GAS_BASE_SET_OUTCOME),
+            request_expiry: U64(DEFAULT_REQUEST_EXPIRY),
+            bond_limits: BondLimits::default(),
+            slash_unrevealed_commits: true,
+            min_commit_share: commit_reveal::DEFAULT_MIN_COMMIT_SHARE,
+        }
+    }
+}
 
 #[near_bindgen]
 impl Contract {
@@ -27,6 +63,30 @@ impl Contract {
         helpers::refund_storage(initial_storage, env::predecessor_account_id());
     }
 
+    pub fn get_extended_config(&self) -> ExtendedOracleConfig {
+        self.extended_config.clone()
+    }
+
+    pub fn set_extended_config(&mut self, new_config: ExtendedOracleConfig) {
+        self.assert_gov();
+        assert!(
+            u64::from(new_config.max_set_outcome_gas) > 0,
+            "max set_outcome gas has to be higher than 0"
+        );
+        assert!(
+            u64::from(new_config.request_expiry) > 0,
+            "request expiry has to be higher than 0"
+        );
+        new_config.bond_limits.assert_valid();
+        assert!(
+            new_config.min_commit_share <= commit_reveal::MIN_COMMIT_SHARE_DIVISOR,
+            "min commit share can't be higher than the first round bond"
+        );
+
+        logger::log_extended_oracle_config(&new_config);
+        self.extended_config = new_config;
+    }
+
     pub fn toggle_pause(&mut self) {
         self.assert_gov();
         self.paused = !self.paused;
@@ -37,63 +97,15 @@ impl Contract {
 #[cfg(test)]
 mod mock_token_basic_tests {
     use super::*;
-    use flux_sdk::config::FeeConfig;
-    use near_sdk::{json_types::U64, testing_env, MockedBlockchain, VMContext};
+    use crate::test_utils::*;
+    use near_sdk::{testing_env, MockedBlockchain};
 
-    fn alice() -> AccountId {
-        "alice.near".to_string()
-    }
-
-    fn bob() -> AccountId {
-        "bob.near".to_string()
-    }
-
-    fn token() -> AccountId {
-        "token.near".to_string()
-    }
-
-    fn gov() -> AccountId {
-        "gov.near".to_string()
-    }
-
-    fn config(gov: AccountId) -> oracle_config::OracleConfig {
-        oracle_config::OracleConfig {
+    fn config(gov: AccountId) -> OracleConfig {
+        OracleConfig {
             gov,
-            final_arbitrator: alice(),
-            payment_token: token(),
-            stake_token: token(),
             validity_bond: U128(1),
-            max_outcomes: 8,
-            default_challenge_window_duration: U64(1000),
-            min_initial_challenge_window_duration: U64(1000),
-            final_arbitrator_invoke_amount: U128(25_000_000_000_000_000_000_000_000_000_000),
-            fee: FeeConfig {
-                flux_market_cap: U128(50000),
-                total_value_staked: U128(10000),
-                resolution_fee_percentage: 5000, // 5%
-            },
-            min_resolution_bond: U128(100),
-        }
-    }
-
-    fn get_context(predecessor_account_id: AccountId) -> VMContext {
-        VMContext {
-            current_account_id: token(),
-            signer_account_id: bob(),
-            signer_account_pk: vec![0, 1, 2],
-            predecessor_account_id,
-            input: vec![],
-            block_index: 0,
-            block_timestamp: 0,
-            account_balance: 1000 * 10u128.pow(24),
-            account_locked_balance: 0,
-            storage_usage: 10u64.pow(6),
-            attached_deposit: 19000000000000000000000,
-            prepaid_gas: 10u64.pow(18),
-            random_seed: vec![0, 1, 2],
-            is_view: false,
-            output_data_receivers: vec![],
-            epoch_height: 0,
+            fee: fee_config(5000), // 5%
+            ..new_config(25_000_000_000_000_000_000_000_000_000_000)
         }
     }
 
@@ -112,4 +124,31 @@ mod mock_token_basic_tests {
         let mut contract = Contract::new(None, config(gov()));
         contract.set_config(config(alice()));
     }
+
+    #[test]
+    fn set_extended_config_from_gov() {
+        testing_env!(get_context(gov()));
+        let mut contract = Contract::new(None, config(gov()));
+        assert_eq!(
+            contract.get_extended_config().max_set_outcome_gas,
+            U64(GAS_BASE_SET_OUTCOME)
+        );
+
+        contract.set_extended_config(ExtendedOracleConfig {
+            max_set_outcome_gas: U64(10),
+            ..ExtendedOracleConfig::default()
+        });
+        assert_eq!(contract.get_extended_config().max_set_outcome_gas, U64(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "This method is only callable by the governance contract gov.near")]
+    fn fail_set_extended_config_from_user() {
+        testing_env!(get_context(alice()));
+        let mut contract = Contract::new(None, config(gov()));
+        contract.set_extended_config(ExtendedOracleConfig {
+            max_set_outcome_gas: U64(10),
+            ..ExtendedOracleConfig::default()
+        });
+    }
 }
@@ -0,0 +1,345 @@
+use crate::*;
+use crate::requester_handler::RequesterHandler;
+use flux_sdk::{outcome::Outcome, requester::Requester};
+use near_sdk::{
+    ext_contract,
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+    Gas, Promise,
+};
+
+/// Gas reserved for the callback that records whether `set_outcome` succeeded
+pub const GAS_BASE_RESOLVE_DELIVERY: Gas = 10_000_000_000_000;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+    AwaitingReveal, // the data request finalized on a digest of which the payload has not been revealed yet
+}
+
+/// Delivery of the final outcome of a data request to its requester through `set_outcome`
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OutcomeDelivery {
+    pub requester: Requester,
+    pub tags: Vec<String>,
+    pub status: DeliveryStatus,
+    pub finalized_at: U64,
+    // whether the requester got its validity bond back, which is only the case for Invalid if the request expired
+    pub validity_bond_returned: bool,
+}
+
+/// Outcome of a data request as returned to requesters that query it rather than wait for `set_outcome`
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OutcomeResult {
+    pub request_id: U64,
+    pub outcome: Option<Outcome>, // `None` while the data request is not finalized
+    pub finalized_at: Option<U64>,
+    pub requester: Option<AccountId>,
+    pub tags: Option<Vec<String>>,
+    pub delivery_status: Option<DeliveryStatus>,
+}
+
+#[ext_contract(ext_self)]
+trait SelfExt {
+    fn dr_resolve_outcome_delivery(request_id: U64) -> bool;
+}
+
+impl Contract {
+    /**
+     * @notice Calls `set_outcome` on the requester and records whether it succeeded once it has been executed
+     */
+    pub fn deliver_outcome(
+        &mut self,
+        request_id: u64,
+        requester: Requester,
+        outcome: Outcome,
+        tags: Vec<String>,
+        validity_bond_returned: bool,
+        gas: Gas,
+    ) -> Promise {
+        let finalized_at = match self.deliveries.get(&request_id) {
+            Some(delivery) => delivery.finalized_at,
+            None => U64(env::block_timestamp()),
+        };
+        let delivery = OutcomeDelivery {
+            requester,
+            tags,
+            status: DeliveryStatus::Pending,
+            finalized_at,
+            validity_bond_returned,
+        };
+        self.deliveries.insert(&request_id, &delivery);
+        logger::log_outcome_delivery(request_id, &delivery);
+
+        delivery
+            .requester
+            .set_outcome(
+                outcome,
+                delivery.tags.clone(),
+                delivery.validity_bond_returned,
+                gas,
+            )
+            .then(ext_self::dr_resolve_outcome_delivery(
+                U64(request_id),
+                // NEAR params
+                &env::current_account_id(),
+                0,
+                GAS_BASE_RESOLVE_DELIVERY,
+            ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * @notice Records whether `set_outcome` succeeded on the requester
+     * @returns whether the outcome was delivered
+     */
+    #[private]
+    pub fn dr_resolve_outcome_delivery(&mut self, request_id: U64) -> bool {
+        let mut delivery = self
+            .deliveries
+            .get(&request_id.into())
+            .expect("ERR_NO_OUTCOME_DELIVERY");
+        let delivered = helpers::is_promise_success();
+
+        if delivered {
+            delivery.status = DeliveryStatus::Delivered;
+            self.failed_deliveries.remove(&request_id.into());
+        } else {
+            delivery.status = DeliveryStatus::Failed;
+            self.failed_deliveries.insert(&request_id.into());
+        }
+
+        self.deliveries.insert(&request_id.into(), &delivery);
+        logger::log_outcome_delivery(request_id.into(), &delivery);
+
+        delivered
+    }
+
+    /**
+     * @notice Retries calling `set_outcome` on the requester of a data request of which the delivery failed
+     */
+    pub fn dr_redeliver_outcome(&mut self, request_id: U64) -> Promise {
+        self.assert_unpaused();
+        let delivery = self
+            .deliveries
+            .get(&request_id.into())
+            .expect("ERR_NO_OUTCOME_DELIVERY");
+        assert_eq!(
+            delivery.status,
+            DeliveryStatus::Failed,
+            "only failed deliveries can be retried"
+        );
+
+        let finalized_outcome = match self.data_requests.get(request_id.into()) {
+            Some(DataRequest::Finalized(dr)) => dr.finalized_outcome,
+            _ => panic!("Error DataRequest is not yet finalized"),
+        };
+        let outcome = self
+            .deliverable_outcome(request_id.into(), finalized_outcome)
+            .expect("ERR_PAYLOAD_NOT_REVEALED");
+        let gas = self.assert_set_outcome_gas(&delivery.requester.account_id, 0);
+        self.failed_deliveries.remove(&request_id.into());
+
+        self.deliver_outcome(
+            request_id.into(),
+            delivery.requester,
+            outcome,
+            delivery.tags,
+            delivery.validity_bond_returned,
+            gas,
+        )
+    }
+
+    pub fn get_outcome_delivery(&self, request_id: U64) -> Option<OutcomeDelivery> {
+        self.deliveries.get(&request_id.into())
+    }
+
+    /**
+     * @notice Lets requesters pull the outcome of a data request, e.g. when they missed `set_outcome`
+     * @returns the outcome and finalization metadata, with `outcome` set to `None` if the data request does not exist or is not finalized
+     */
+    pub fn get_outcome_result(&self, request_id: U64) -> OutcomeResult {
+        let outcome = match self.data_requests.get(request_id.into()) {
+            Some(DataRequest::Finalized(dr)) => Some(dr.finalized_outcome),
+            _ => None,
+        };
+        // data requests finalized before deliveries were tracked have no delivery
+        let delivery = match outcome {
+            Some(_) => self.deliveries.get(&request_id.into()),
+            None => None,
+        };
+
+        OutcomeResult {
+            request_id,
+            outcome,
+            finalized_at: delivery.as_ref().map(|d| d.finalized_at),
+            requester: delivery.as_ref().map(|d| d.requester.account_id.to_string()),
+            tags: delivery.as_ref().map(|d| d.tags.clone()),
+            delivery_status: delivery.map(|d| d.status),
+        }
+    }
+
+    // @returns the outcome results of `request_ids` in the same order
+    pub fn get_outcome_results(&self, request_ids: Vec<U64>) -> Vec<OutcomeResult> {
+        request_ids
+            .into_iter()
+            .map(|request_id| self.get_outcome_result(request_id))
+            .collect()
+    }
+
+    // @returns ids of data requests of which the outcome could not be delivered to the requester
+    pub fn get_failed_deliveries(&self, from_index: U64, limit: U64) -> Vec<U64> {
+        let failed = self.failed_deliveries.as_vector();
+        let i: u64 = from_index.into();
+        (i..std::cmp::min(i + u64::from(limit), failed.len()))
+            .map(|index| U64(failed.get(index).unwrap()))
+            .collect()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use crate::test_utils::*;
+    use flux_sdk::{
+        data_request::{DataRequestDataType, NewDataRequestArgs, StakeDataRequestArgs},
+        outcome::AnswerType,
+    };
+    use near_sdk::{json_types::U128, testing_env, MockedBlockchain, PromiseResult};
+
+    fn finalized_contract() -> Contract {
+        testing_env!(get_context_at(token(), 0));
+        let whitelist = Some(vec![registry_entry(bob())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        contract.dr_new(
+            bob(),
+            100,
+            NewDataRequestArgs {
+                sources: Some(Vec::new()),
+                outcomes: Some(vec!["a".to_string(), "b".to_string()]),
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
+                data_type: DataRequestDataType::String,
+                provider: None,
+            },
+        );
+        contract.dr_stake(
+            alice(),
+            200,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: Outcome::Answer(AnswerType::String("a".to_string())),
+            },
+        );
+
+        testing_env!(get_context_at(token(), 1501));
+        contract.dr_finalize(U64(0));
+        contract
+    }
+
+    fn resolve_delivery(contract: &mut Contract, result: PromiseResult) -> bool {
+        testing_env!(
+            get_context_at(token(), 1501),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.dr_resolve_outcome_delivery(U64(0))
+    }
+
+    #[test]
+    fn delivery_pending_after_finalize() {
+        let contract = finalized_contract();
+
+        let delivery = contract.get_outcome_delivery(U64(0)).unwrap();
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.requester.account_id, bob());
+        assert_eq!(delivery.tags, vec!["1".to_string()]);
+        assert_eq!(delivery.finalized_at, U64(1501));
+        assert!(delivery.validity_bond_returned);
+    }
+
+    #[test]
+    fn delivery_succeeded() {
+        let mut contract = finalized_contract();
+
+        assert!(resolve_delivery(&mut contract, PromiseResult::Successful(vec![])));
+        assert_eq!(
+            contract.get_outcome_delivery(U64(0)).unwrap().status,
+            DeliveryStatus::Delivered
+        );
+        assert!(contract.get_failed_deliveries(U64(0), U64(10)).is_empty());
+    }
+
+    #[test]
+    fn delivery_failed_and_redelivered() {
+        let mut contract = finalized_contract();
+
+        assert!(!resolve_delivery(&mut contract, PromiseResult::Failed));
+        assert_eq!(
+            contract.get_outcome_delivery(U64(0)).unwrap().status,
+            DeliveryStatus::Failed
+        );
+        assert_eq!(contract.get_failed_deliveries(U64(0), U64(10)), vec![U64(0)]);
+
+        testing_env!(get_context_at(alice(), 2000));
+        contract.dr_redeliver_outcome(U64(0));
+        let delivery = contract.get_outcome_delivery(U64(0)).unwrap();
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.finalized_at, U64(1501));
+        assert!(contract.get_failed_deliveries(U64(0), U64(10)).is_empty());
+
+        assert!(resolve_delivery(&mut contract, PromiseResult::Successful(vec![])));
+        assert_eq!(
+            contract.get_outcome_delivery(U64(0)).unwrap().status,
+            DeliveryStatus::Delivered
+        );
+    }
+
+    #[test]
+    fn outcome_result_finalized() {
+        let contract = finalized_contract();
+
+        assert_eq!(
+            contract.get_outcome_result(U64(0)),
+            OutcomeResult {
+                request_id: U64(0),
+                outcome: Some(Outcome::Answer(AnswerType::String("a".to_string()))),
+                finalized_at: Some(U64(1501)),
+                requester: Some(bob()),
+                tags: Some(vec!["1".to_string()]),
+                delivery_status: Some(DeliveryStatus::Pending),
+            }
+        );
+    }
+
+    #[test]
+    fn outcome_results_not_finalized() {
+        let contract = finalized_contract();
+
+        let results = contract.get_outcome_results(vec![U64(1), U64(0)]);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].request_id, U64(1));
+        assert_eq!(results[0].outcome, None);
+        assert_eq!(results[0].finalized_at, None);
+        assert_eq!(results[1].request_id, U64(0));
+        assert!(results[1].outcome.is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "only failed deliveries can be retried")]
+    fn redeliver_pending() {
+        let mut contract = finalized_contract();
+        contract.dr_redeliver_outcome(U64(0));
+    }
+}
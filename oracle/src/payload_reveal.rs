@@ -0,0 +1,238 @@
+use crate::answer_format::AnswerFormat;
+use crate::outcome_delivery::{DeliveryStatus, OutcomeDelivery};
+use crate::*;
+use flux_sdk::{
+    outcome::{AnswerType, Outcome},
+    requester::Requester,
+};
+use near_sdk::{json_types::U64, Gas, Promise};
+
+/// Length of the sha256 digest a payload hash request is staked on
+pub const DIGEST_LENGTH: usize = 32;
+
+// @returns the lowercase hex encoded sha256 digest of `payload`
+pub fn digest(payload: &str) -> String {
+    helpers::sha256_hex(payload.as_bytes())
+}
+
+// @returns whether `answer` is a lowercase hex encoded digest
+pub fn is_digest(answer: &str) -> bool {
+    answer.len() == DIGEST_LENGTH * 2
+        && answer
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+impl Contract {
+    // @returns whether `outcome` is a digest of which the payload has to be revealed before it's delivered
+    fn awaits_reveal(&self, dr_id: u64, outcome: &Outcome) -> bool {
+        self.answer_formats.get(&dr_id) == Some(AnswerFormat::PayloadHash)
+            && matches!(outcome, Outcome::Answer(_))
+    }
+
+    /**
+     * @notice Outcome the requester of data request `dr_id` receives through `set_outcome`, the revealed payload
+     * instead of its digest for payload hash requests
+     * @returns `None` if the payload of the digest has not been revealed yet
+     */
+    pub fn deliverable_outcome(&self, dr_id: u64, finalized_outcome: Outcome) -> Option<Outcome> {
+        if !self.awaits_reveal(dr_id, &finalized_outcome) {
+            return Some(finalized_outcome);
+        }
+        self.revealed_payloads
+            .get(&dr_id)
+            .map(|payload| Outcome::Answer(AnswerType::String(payload)))
+    }
+
+    /**
+     * @notice Delivers the final outcome of a data request to its requester, unless it is a digest, which is only
+     * delivered as its payload once that has been revealed through `dr_reveal_payload`
+     * @returns the `set_outcome` promise, `None` if the delivery awaits the payload
+     */
+    pub fn deliver_final_outcome(
+        &mut self,
+        request_id: u64,
+        requester: Requester,
+        outcome: Outcome,
+        tags: Vec<String>,
+        gas: Gas,
+    ) -> Option<Promise> {
+        // the validity bond is returned if the request resolved to an answer
+        let validity_bond_returned = match outcome {
+            Outcome::Answer(_) => true,
+            Outcome::Invalid => false,
+        };
+        if self.awaits_reveal(request_id, &outcome) {
+            let delivery = OutcomeDelivery {
+                requester,
+                tags,
+                status: DeliveryStatus::AwaitingReveal,
+                finalized_at: U64(env::block_timestamp()),
+                validity_bond_returned,
+            };
+            self.deliveries.insert(&request_id, &delivery);
+            logger::log_outcome_delivery(request_id, &delivery);
+            return None;
+        }
+        Some(self.deliver_outcome(
+            request_id,
+            requester,
+            outcome,
+            tags,
+            validity_bond_returned,
+            gas,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * @notice Reveals the payload of which the digest a payload hash request finalized on and delivers it to the
+     * requester. Callable by anyone, the storage of the payload is paid by the caller
+     */
+    #[payable]
+    pub fn dr_reveal_payload(&mut self, request_id: U64, payload: String) -> Promise {
+        self.assert_unpaused();
+        let initial_storage = env::storage_usage();
+        let finalized_outcome = match self.data_requests.get(request_id.into()) {
+            Some(DataRequest::Finalized(dr)) => dr.finalized_outcome,
+            _ => panic!("Error DataRequest is not yet finalized"),
+        };
+        assert!(
+            self.awaits_reveal(request_id.into(), &finalized_outcome),
+            "Error DataRequest did not finalize on a payload digest"
+        );
+        let delivery = self
+            .deliveries
+            .get(&request_id.into())
+            .expect("ERR_NO_OUTCOME_DELIVERY");
+        assert_eq!(
+            delivery.status,
+            DeliveryStatus::AwaitingReveal,
+            "payload has already been revealed"
+        );
+        match &finalized_outcome {
+            Outcome::Answer(AnswerType::String(finalized_digest)) => assert_eq!(
+                &digest(&payload),
+                finalized_digest,
+                "payload does not match the finalized digest"
+            ),
+            _ => panic!("ERR_OUTCOME_NOT_STRING"),
+        }
+
+        self.revealed_payloads.insert(&request_id.into(), &payload);
+        logger::log_payload_reveal(request_id.into(), &payload);
+        helpers::refund_storage(initial_storage, env::predecessor_account_id());
+
+        let gas = self.assert_set_outcome_gas(&delivery.requester.account_id, 0);
+        self.deliver_outcome(
+            request_id.into(),
+            delivery.requester,
+            Outcome::Answer(AnswerType::String(payload)),
+            delivery.tags,
+            delivery.validity_bond_returned,
+            gas,
+        )
+    }
+
+    pub fn get_revealed_payload(&self, request_id: U64) -> Option<String> {
+        self.revealed_payloads.get(&request_id.into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use crate::data_request::DataRequestSettings;
+    use crate::test_utils::*;
+    use flux_sdk::data_request::{DataRequestDataType, NewDataRequestArgs, StakeDataRequestArgs};
+    use near_sdk::{json_types::U128, testing_env, MockedBlockchain};
+
+    fn payload() -> String {
+        "team,points\na,3\nb,1".to_string()
+    }
+
+    fn finalized_contract(staked_digest: String) -> Contract {
+        testing_env!(get_context_at(token(), 0));
+        let whitelist = Some(vec![registry_entry(bob())]);
+        let mut contract = Contract::new(whitelist, zero_fee_config());
+        contract.dr_new_with_settings(
+            bob(),
+            100,
+            NewDataRequestArgs {
+                sources: Some(Vec::new()),
+                outcomes: None,
+                challenge_period: U64(1500),
+                description: Some("a".to_string()),
+                tags: vec!["1".to_string()],
+                data_type: DataRequestDataType::String,
+                provider: None,
+            },
+            DataRequestSettings {
+                answer_format: Some(AnswerFormat::PayloadHash),
+                ..DataRequestSettings::default()
+            },
+        );
+        contract.dr_stake(
+            alice(),
+            200,
+            StakeDataRequestArgs {
+                id: U64(0),
+                outcome: Outcome::Answer(AnswerType::String(staked_digest)),
+            },
+        );
+
+        testing_env!(get_context_at(token(), 1501));
+        contract.dr_finalize(U64(0));
+        contract
+    }
+
+    #[test]
+    fn digest_format() {
+        testing_env!(get_context_at(token(), 0));
+        assert!(is_digest(&digest(&payload())));
+        assert!(!is_digest(&digest(&payload()).to_uppercase()));
+        assert!(!is_digest("abc"));
+    }
+
+    #[test]
+    fn reveal_payload() {
+        testing_env!(get_context_at(token(), 0));
+        let mut contract = finalized_contract(digest(&payload()));
+        assert_eq!(
+            contract.get_outcome_delivery(U64(0)).unwrap().status,
+            DeliveryStatus::AwaitingReveal
+        );
+
+        contract.dr_reveal_payload(U64(0), payload());
+        assert_eq!(contract.get_revealed_payload(U64(0)), Some(payload()));
+        let delivery = contract.get_outcome_delivery(U64(0)).unwrap();
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.finalized_at, U64(1501));
+    }
+
+    #[test]
+    #[should_panic(expected = "payload does not match the finalized digest")]
+    fn reveal_wrong_payload() {
+        testing_env!(get_context_at(token(), 0));
+        let mut contract = finalized_contract(digest(&payload()));
+        contract.dr_reveal_payload(U64(0), "team,points".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "payload has already been revealed")]
+    fn reveal_payload_twice() {
+        testing_env!(get_context_at(token(), 0));
+        let mut contract = finalized_contract(digest(&payload()));
+        contract.dr_reveal_payload(U64(0), payload());
+        contract.dr_reveal_payload(U64(0), payload());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_OUTCOME_NOT_DIGEST")]
+    fn stake_not_a_digest() {
+        finalized_contract(payload());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::*;
+use flux_sdk::outcome::Outcome;
+use near_sdk::{
+    json_types::U64,
+    serde::{Deserialize, Serialize},
+};
+
+/// Maximum number of providers in a quorum, bounds the gas of counting their submissions
+pub const MAX_QUORUM_PROVIDERS: usize = 16;
+
+/// Providers of a data request of which `threshold` have to submit the same outcome for it to get bonded
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProviderQuorumSettings {
+    pub providers: Vec<AccountId>,
+    pub threshold: u16,
+    pub submission_duration: U64, // time after creation in which providers can submit, staking opens after it
+    pub challenge_duration: U64, // duration of the window in which stakers can dispute the agreed outcome
+}
+
+impl ProviderQuorumSettings {
+    pub fn assert_valid(&self) {
+        assert!(
+            !self.providers.is_empty() && self.providers.len() <= MAX_QUORUM_PROVIDERS,
+            "provider quorum needs between 1 and {} providers",
+            MAX_QUORUM_PROVIDERS
+        );
+        for (i, provider) in self.providers.iter().enumerate() {
+            assert!(
+                !self.providers[..i].contains(provider),
+                "{} is listed as provider twice",
+                provider
+            );
+        }
+        assert!(
+            self.threshold > 0 && self.threshold as usize <= self.providers.len(),
+            "threshold has to be between 1 and the number of providers"
+        );
+        assert!(
+            u64::from(self.submission_duration) > 0,
+            "submission duration has to be higher than 0"
+        );
+        assert!(
+            u64::from(self.challenge_duration) > 0,
+            "challenge duration has to be higher than 0"
+        );
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProviderSubmission {
+    pub account_id: AccountId,
+    pub outcome: Outcome,
+}
+
+/// Outcomes submitted by the providers of a data request, the first round is bonded on their outcome once they agree
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProviderQuorum {
+    pub providers: Vec<AccountId>,
+    pub threshold: u16,
+    pub submission_end: U64,
+    pub challenge_duration: U64,
+    pub submissions: Vec<ProviderSubmission>,
+    pub agreed_outcome: Option<Outcome>,
+}
+
+impl ProviderQuorum {
+    // @returns the outcome that at least `threshold` providers submitted, if any
+    pub fn find_agreement(&self) -> Option<Outcome> {
+        self.submissions
+            .iter()
+            .map(|submission| &submission.outcome)
+            .find(|outcome| {
+                self.submissions
+                    .iter()
+                    .filter(|submission| &submission.outcome == *outcome)
+                    .count()
+                    >= self.threshold as usize
+            })
+            .cloned()
+    }
+}
+
+impl Contract {
+    // @notice opens the submission phase of a data request created at the current block
+    pub fn init_provider_quorum(&mut self, dr_id: u64, settings: &ProviderQuorumSettings) {
+        self.provider_quorums.insert(
+            &dr_id,
+            &ProviderQuorum {
+                providers: settings.providers.clone(),
+                threshold: settings.threshold,
+                submission_end: U64(
+                    env::block_timestamp() + u64::from(settings.submission_duration)
+                ),
+                challenge_duration: settings.challenge_duration,
+                submissions: Vec::new(),
+                agreed_outcome: None,
+            },
+        );
+    }
+
+    // panics if the providers of data request `dr_id` can still agree on an outcome
+    pub fn assert_provider_quorum_closed(&self, dr_id: u64) {
+        if let Some(quorum) = self.provider_quorums.get(&dr_id) {
+            assert!(
+                quorum.agreed_outcome.is_some()
+                    || env::block_timestamp() >= u64::from(quorum.submission_end),
+                "Error DataRequest is open for provider submissions"
+            );
+        }
+    }
+
+    /**
+     * @notice Checks whether data request `dr_id` resolved on the outcome its providers agreed on without anyone
+     * disputing it, the first round is then only bonded by the providers and no stake earned the fee
+     * @returns whether the request has `window_count` windows of which only the providers' round is bonded
+     */
+    pub fn is_unchallenged_quorum(&self, dr_id: u64, window_count: u64) -> bool {
+        self.provider_quorums
+            .get(&dr_id)
+            .map(|quorum| quorum.agreed_outcome.is_some() && window_count == 2)
+            .unwrap_or(false)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_provider_quorum(&self, request_id: U64) -> Option<ProviderQuorum> {
+        self.provider_quorums.get(&request_id.into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use flux_sdk::outcome::AnswerType;
+
+    fn answer(answer: &str) -> Outcome {
+        Outcome::Answer(AnswerType::String(answer.to_string()))
+    }
+
+    fn settings(providers: Vec<&str>, threshold: u16) -> ProviderQuorumSettings {
+        ProviderQuorumSettings {
+            providers: providers.into_iter().map(String::from).collect(),
+            threshold,
+            submission_duration: U64(100),
+            challenge_duration: U64(100),
+        }
+    }
+
+    fn quorum(submissions: Vec<(&str, Outcome)>) -> ProviderQuorum {
+        ProviderQuorum {
+            providers: vec![
+                "alice.near".to_string(),
+                "bob.near".to_string(),
+                "carol.near".to_string(),
+            ],
+            threshold: 2,
+            submission_end: U64(100),
+            challenge_duration: U64(100),
+            submissions: submissions
+                .into_iter()
+                .map(|(account_id, outcome)| ProviderSubmission {
+                    account_id: account_id.to_string(),
+                    outcome,
+                })
+                .collect(),
+            agreed_outcome: None,
+        }
+    }
+
+    #[test]
+    fn agreement_reached() {
+        let quorum = quorum(vec![
+            ("alice.near", answer("a")),
+            ("bob.near", answer("b")),
+            ("carol.near", answer("b")),
+        ]);
+        assert_eq!(quorum.find_agreement(), Some(answer("b")));
+    }
+
+    #[test]
+    fn agreement_not_reached() {
+        let quorum = quorum(vec![("alice.near", answer("a")), ("bob.near", answer("b"))]);
+        assert_eq!(quorum.find_agreement(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "threshold has to be between 1 and the number of providers")]
+    fn threshold_above_providers() {
+        settings(vec!["alice.near", "bob.near"], 3).assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = "alice.near is listed as provider twice")]
+    fn duplicate_provider() {
+        settings(vec!["alice.near", "alice.near"], 1).assert_valid();
+    }
+}
@@ -1,13 +1,18 @@
+use crate::data_request::DataRequestSettings;
 use crate::*;
 use flux_sdk::{
-    consts::GAS_BASE_SET_OUTCOME, data_request::NewDataRequestArgs, outcome::Outcome,
-    requester::Requester, types::WrappedBalance,
+    data_request::NewDataRequestArgs, outcome::Outcome, requester::Requester, types::WrappedBalance,
 };
-use near_sdk::{ext_contract, Promise, PromiseOrValue};
+use near_sdk::{ext_contract, Gas, Promise, PromiseOrValue};
 
 #[ext_contract]
 pub trait RequesterContractExtern {
-    fn set_outcome(requester: AccountId, outcome: Outcome, tags: Vec<String>);
+    fn set_outcome(
+        requester: AccountId,
+        outcome: Outcome,
+        tags: Vec<String>,
+        validity_bond_returned: bool,
+    );
 }
 
 #[ext_contract(ext_self)]
@@ -17,7 +22,13 @@ trait SelfExt {
 
 pub trait RequesterHandler {
     fn new_no_whitelist(account_id: &AccountId) -> Self;
-    fn set_outcome(&self, outcome: Outcome, tags: Vec<String>) -> Promise;
+    fn set_outcome(
+        &self,
+        outcome: Outcome,
+        tags: Vec<String>,
+        validity_bond_returned: bool,
+        gas: Gas,
+    ) -> Promise;
 }
 
 impl RequesterHandler for Requester {
@@ -29,21 +40,22 @@ impl RequesterHandler for Requester {
             code_base_url: None,
         }
     }
-    fn set_outcome(&self, outcome: Outcome, tags: Vec<String>) -> Promise {
-        // AUDIT: Suggestions:
-        //     - `1` yoctoNEAR is not necessary, since this callback can only be received from the oracle and not from the user.
-        //     - Gas limit is a bit tight. Ideally there is larger amount of gas that can be configured.
-        // SOLUTION:
-        //     - remove 1 yoctoNEAR
-        //     - Figure out how to get ideal gas amount and implement
+    fn set_outcome(
+        &self,
+        outcome: Outcome,
+        tags: Vec<String>,
+        validity_bond_returned: bool,
+        gas: Gas,
+    ) -> Promise {
         requester_contract_extern::set_outcome(
             self.account_id.to_string(),
             outcome,
             tags,
+            validity_bond_returned,
             // NEAR params
             &self.account_id,
-            1,
-            GAS_BASE_SET_OUTCOME / 10,
+            0,
+            gas,
         )
     }
 }
@@ -59,7 +71,13 @@ impl Contract {
         sender: AccountId,
         amount: Balance,
         payload: NewDataRequestArgs,
+        settings: DataRequestSettings,
     ) -> PromiseOrValue<WrappedBalance> {
-        PromiseOrValue::Value(U128(self.dr_new(sender.clone(), amount.into(), payload)))
+        PromiseOrValue::Value(U128(self.dr_new_with_settings(
+            sender.clone(),
+            amount.into(),
+            payload,
+            settings,
+        )))
     }
 }
@@ -1,4 +1,4 @@
-use crate::logger;
+use crate::{bond_settings::BOND_GROWTH_DIVISOR, helpers, logger, Contract};
 use flux_sdk::{
     outcome::Outcome,
     resolution_window::{CorrectStake, ResolutionWindow, WindowStakeResult},
@@ -10,13 +10,44 @@ pub trait ResolutionWindowHandler {
         dr_id: u64,
         round: u16,
         prev_bond: Balance,
+        bond_growth: u32,
         challenge_period: u64,
         start_time: u64,
     ) -> Self;
     fn get_user_to_outcomes(&self, sender: &AccountId) -> LookupMap<Outcome, Balance>;
-    fn stake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance) -> Balance;
-    fn unstake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance) -> Balance;
-    fn claim_for(&mut self, account_id: AccountId, final_outcome: &Outcome) -> WindowStakeResult;
+    fn stake(
+        &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        outcome: Outcome,
+        amount: Balance,
+    ) -> Balance;
+    fn unstake(
+        &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        outcome: Outcome,
+        amount: Balance,
+    ) -> Balance;
+    fn restore_stake(
+        &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        outcome: Outcome,
+        amount: Balance,
+    );
+    fn claim_for(
+        &mut self,
+        contract: &Contract,
+        account_id: AccountId,
+        final_outcome: &Outcome,
+    ) -> WindowStakeResult;
+    fn stake_result_for(
+        &self,
+        contract: &Contract,
+        account_id: &AccountId,
+        final_outcome: &Outcome,
+    ) -> WindowStakeResult;
 }
 
 impl ResolutionWindowHandler for ResolutionWindow {
@@ -24,6 +55,7 @@ impl ResolutionWindowHandler for ResolutionWindow {
         dr_id: u64,
         round: u16,
         prev_bond: Balance,
+        bond_growth: u32,
         challenge_period: u64,
         start_time: u64,
     ) -> Self {
@@ -32,7 +64,11 @@ impl ResolutionWindowHandler for ResolutionWindow {
             round,
             start_time,
             end_time: start_time + challenge_period,
-            bond_size: prev_bond * 2,
+            bond_size: helpers::calc_product(
+                prev_bond,
+                bond_growth.into(),
+                BOND_GROWTH_DIVISOR.into(),
+            ),
             outcome_to_stake: LookupMap::new(format!("ots{}:{}", dr_id, round).as_bytes().to_vec()),
             user_to_outcome_to_stake: LookupMap::new(
                 format!("utots{}:{}", dr_id, round).as_bytes().to_vec(),
@@ -55,7 +91,13 @@ impl ResolutionWindowHandler for ResolutionWindow {
     }
 
     // @returns amount to refund users because it was not staked
-    fn stake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance) -> Balance {
+    fn stake(
+        &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        outcome: Outcome,
+        amount: Balance,
+    ) -> Balance {
         let stake_on_outcome = self.outcome_to_stake.get(&outcome).unwrap_or(0);
         let user_stake_on_outcome = self
             .get_user_to_outcomes(&sender)
@@ -81,6 +123,13 @@ impl ResolutionWindowHandler for ResolutionWindow {
             .insert(&outcome, &new_user_stake_on_outcome);
         self.user_to_outcome_to_stake
             .insert(&sender, &self.get_user_to_outcomes(&sender));
+        contract.set_account_stake(
+            &sender,
+            self.dr_id,
+            self.round,
+            &outcome,
+            new_user_stake_on_outcome,
+        );
 
         logger::log_user_stake(
             self.dr_id,
@@ -91,8 +140,11 @@ impl ResolutionWindowHandler for ResolutionWindow {
         );
         logger::log_stake_transaction(&sender, &self, amount, unspent, &outcome);
 
+        contract.update_user_stake(self.dr_id, &sender, self.round, &outcome, staked, true);
+
         // If this stake fills the bond set final outcome which will trigger a new resolution_window to be created
         if new_stake_on_outcome == self.bond_size {
+            contract.add_bonded(self.dr_id, self.round, &outcome, self.bond_size);
             self.bonded_outcome = Some(outcome);
             logger::log_resolution_window(&self);
         }
@@ -101,7 +153,13 @@ impl ResolutionWindowHandler for ResolutionWindow {
     }
 
     // @returns amount to refund users because it was not staked
-    fn unstake(&mut self, sender: AccountId, outcome: Outcome, amount: Balance) -> Balance {
+    fn unstake(
+        &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        outcome: Outcome,
+        amount: Balance,
+    ) -> Balance {
         assert!(
             self.bonded_outcome.is_none() || self.bonded_outcome.as_ref().unwrap() != &outcome,
             "Cannot withdraw from bonded outcome"
@@ -129,6 +187,13 @@ impl ResolutionWindowHandler for ResolutionWindow {
             .insert(&outcome, &new_user_stake_on_outcome);
         self.user_to_outcome_to_stake
             .insert(&sender, &self.get_user_to_outcomes(&sender));
+        contract.reduce_account_stake(
+            &sender,
+            self.dr_id,
+            self.round,
+            &outcome,
+            new_user_stake_on_outcome,
+        );
         logger::log_user_stake(
             self.dr_id,
             self.round,
@@ -137,16 +202,61 @@ impl ResolutionWindowHandler for ResolutionWindow {
             new_user_stake_on_outcome,
         );
         logger::log_unstake_transaction(&sender, &self, amount, &outcome);
+        contract.update_user_stake(self.dr_id, &sender, self.round, &outcome, amount, false);
 
         amount
     }
 
-    fn claim_for(&mut self, account_id: AccountId, final_outcome: &Outcome) -> WindowStakeResult {
+    // @notice puts back stake that was unstaked but could not be transferred to `sender`
+    fn restore_stake(
+        &mut self,
+        contract: &mut Contract,
+        sender: AccountId,
+        outcome: Outcome,
+        amount: Balance,
+    ) {
+        let new_stake_on_outcome = self.outcome_to_stake.get(&outcome).unwrap_or(0) + amount;
+        self.outcome_to_stake
+            .insert(&outcome, &new_stake_on_outcome);
+        logger::log_outcome_to_stake(self.dr_id, self.round, &outcome, new_stake_on_outcome);
+
+        let new_user_stake_on_outcome = self
+            .get_user_to_outcomes(&sender)
+            .get(&outcome)
+            .unwrap_or(0)
+            + amount;
+        self.get_user_to_outcomes(&sender)
+            .insert(&outcome, &new_user_stake_on_outcome);
+        self.user_to_outcome_to_stake
+            .insert(&sender, &self.get_user_to_outcomes(&sender));
+        contract.set_account_stake(
+            &sender,
+            self.dr_id,
+            self.round,
+            &outcome,
+            new_user_stake_on_outcome,
+        );
+        logger::log_user_stake(
+            self.dr_id,
+            self.round,
+            &sender,
+            &outcome,
+            new_user_stake_on_outcome,
+        );
+        contract.update_user_stake(self.dr_id, &sender, self.round, &outcome, amount, true);
+    }
+
+    fn claim_for(
+        &mut self,
+        contract: &Contract,
+        account_id: AccountId,
+        final_outcome: &Outcome,
+    ) -> WindowStakeResult {
         // Check if there is a bonded outcome, if there is none it means it can be ignored in payout calc since it can only be the final unsuccessful window
         match &self.bonded_outcome {
             Some(bonded_outcome) => {
                 // If the bonded outcome for this window is equal to the finalized outcome the user's stake in this window and the total amount staked should be returned (which == `self.bond_size`)
-                if bonded_outcome == final_outcome {
+                if contract.outcomes_match(self.dr_id, bonded_outcome, final_outcome) {
                     WindowStakeResult::Correct(CorrectStake {
                         bonded_stake: self.bond_size,
                         // Get the users stake in this outcome for this window
@@ -165,4 +275,31 @@ impl ResolutionWindowHandler for ResolutionWindow {
             None => WindowStakeResult::NoResult, // Return `NoResult` for non-bonded window
         }
     }
+
+    // @returns what `claim_for` would return for `account_id` without removing its stake
+    fn stake_result_for(
+        &self,
+        contract: &Contract,
+        account_id: &AccountId,
+        final_outcome: &Outcome,
+    ) -> WindowStakeResult {
+        match &self.bonded_outcome {
+            Some(bonded_outcome) => {
+                if contract.outcomes_match(self.dr_id, bonded_outcome, final_outcome) {
+                    WindowStakeResult::Correct(CorrectStake {
+                        bonded_stake: self.bond_size,
+                        user_stake: match self.user_to_outcome_to_stake.get(account_id) {
+                            Some(outcome_to_stake) => {
+                                outcome_to_stake.get(bonded_outcome).unwrap_or(0)
+                            }
+                            None => 0,
+                        },
+                    })
+                } else {
+                    WindowStakeResult::Incorrect(self.bond_size)
+                }
+            }
+            None => WindowStakeResult::NoResult,
+        }
+    }
 }
@@ -0,0 +1,153 @@
+use crate::*;
+use flux_sdk::outcome::Outcome;
+
+/**
+ * @notice Running stake totals of an account in a data request. Stake in `open_round` is only added to `bonded`
+ * once it is known whether that round got bonded on the staked outcome, which is the next time the totals are read.
+ */
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct UserStakeTotals {
+    // stake on outcomes in rounds that got bonded on that outcome
+    pub bonded: Vec<(Outcome, Balance)>,
+    pub open_round: u16,
+    // stake per outcome in `open_round`
+    pub open: Vec<(Outcome, Balance)>,
+}
+
+// the totals of a data request are kept under prefixes derived from its id, like its resolution windows
+fn bonded_per_outcome(dr_id: u64) -> LookupMap<Outcome, Balance> {
+    LookupMap::new(format!("obt{}:", dr_id).as_bytes().to_vec())
+}
+
+fn round_bonded_outcome(dr_id: u64) -> LookupMap<u16, Outcome> {
+    LookupMap::new(format!("rbo{}:", dr_id).as_bytes().to_vec())
+}
+
+fn user_totals(dr_id: u64) -> LookupMap<AccountId, UserStakeTotals> {
+    LookupMap::new(format!("ust{}:", dr_id).as_bytes().to_vec())
+}
+
+fn amount_of(stakes: &[(Outcome, Balance)], outcome: &Outcome) -> Balance {
+    stakes
+        .iter()
+        .find(|(staked_outcome, _)| staked_outcome == outcome)
+        .map(|(_, amount)| *amount)
+        .unwrap_or(0)
+}
+
+fn set_amount_of(stakes: &mut Vec<(Outcome, Balance)>, outcome: &Outcome, amount: Balance) {
+    stakes.retain(|(staked_outcome, _)| staked_outcome != outcome);
+    if amount > 0 {
+        stakes.push((outcome.clone(), amount));
+    }
+}
+
+// @notice moves the stake in the open round to `bonded` if that round got bonded on the staked outcome
+fn settle(dr_id: u64, totals: &mut UserStakeTotals) {
+    if let Some(bonded_outcome) = round_bonded_outcome(dr_id).get(&totals.open_round) {
+        let settled = amount_of(&totals.open, &bonded_outcome);
+        let bonded = amount_of(&totals.bonded, &bonded_outcome);
+        set_amount_of(&mut totals.bonded, &bonded_outcome, bonded + settled);
+        totals.open.clear();
+    }
+}
+
+impl Contract {
+    // @notice starts keeping stake totals for a new data request
+    pub fn init_stake_totals(&mut self, dr_id: u64) {
+        self.total_bonded.insert(&dr_id, &0);
+    }
+
+    /**
+     * @returns whether stake totals are kept for this data request, if not its claims have to iterate over its windows.
+     * Data requests created before stake totals were introduced have no entry in `total_bonded` and never get one,
+     * claims on them stay O(rounds)
+     */
+    pub fn is_stake_tracked(&self, dr_id: u64) -> bool {
+        self.total_bonded.contains_key(&dr_id)
+    }
+
+    pub fn add_bonded(&mut self, dr_id: u64, round: u16, outcome: &Outcome, bond_size: Balance) {
+        if let Some(total) = self.total_bonded.get(&dr_id) {
+            self.total_bonded.insert(&dr_id, &(total + bond_size));
+            let mut bonded_per_outcome = bonded_per_outcome(dr_id);
+            let bonded = bonded_per_outcome.get(outcome).unwrap_or(0);
+            bonded_per_outcome.insert(outcome, &(bonded + bond_size));
+            round_bonded_outcome(dr_id).insert(&round, outcome);
+        }
+    }
+
+    // @returns the total bond size of rounds bonded on `final_outcome` and of rounds bonded on other outcomes
+    pub fn bonded_totals(&self, dr_id: u64, final_outcome: &Outcome) -> (Balance, Balance) {
+        let total = self.total_bonded.get(&dr_id).unwrap_or(0);
+        let correct = bonded_per_outcome(dr_id).get(final_outcome).unwrap_or(0);
+        (correct, total - correct)
+    }
+
+    /**
+     * @notice Adds `amount` to (or subtracts it from) the stake of `account_id` on `outcome` in round `round`,
+     * changes to rounds before the open round are ignored because they can only be on outcomes that did not get bonded
+     */
+    pub fn update_user_stake(
+        &mut self,
+        dr_id: u64,
+        account_id: &AccountId,
+        round: u16,
+        outcome: &Outcome,
+        amount: Balance,
+        is_increase: bool,
+    ) {
+        if !self.is_stake_tracked(dr_id) {
+            return;
+        }
+
+        let mut user_totals = user_totals(dr_id);
+        let mut totals = user_totals.get(account_id).unwrap_or_default();
+        if round < totals.open_round {
+            return;
+        }
+        if round > totals.open_round {
+            settle(dr_id, &mut totals);
+            totals.open.clear();
+            totals.open_round = round;
+        }
+
+        let open = amount_of(&totals.open, outcome);
+        let new_open = if is_increase {
+            open + amount
+        } else {
+            // totals are removed on claim, after which unbonded stake can still be unstaked
+            open.saturating_sub(amount)
+        };
+        set_amount_of(&mut totals.open, outcome, new_open);
+        user_totals.insert(account_id, &totals);
+    }
+
+    // @returns the stake of `account_id` in rounds bonded on `final_outcome`
+    pub fn user_correct_stake(
+        &self,
+        dr_id: u64,
+        account_id: &AccountId,
+        final_outcome: &Outcome,
+    ) -> Balance {
+        match user_totals(dr_id).get(account_id) {
+            Some(mut totals) => {
+                settle(dr_id, &mut totals);
+                amount_of(&totals.bonded, final_outcome)
+            }
+            None => 0,
+        }
+    }
+
+    // @returns the stake of `account_id` in rounds bonded on `final_outcome` and removes its totals so it can only be claimed once
+    pub fn claim_user_stake(
+        &mut self,
+        dr_id: u64,
+        account_id: &AccountId,
+        final_outcome: &Outcome,
+    ) -> Balance {
+        let correct_stake = self.user_correct_stake(dr_id, account_id, final_outcome);
+        user_totals(dr_id).remove(account_id);
+        correct_stake
+    }
+}
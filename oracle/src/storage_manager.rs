@@ -160,85 +160,23 @@ impl Contract {
 #[cfg(test)]
 mod mock_token_basic_tests {
     use super::*;
-    use flux_sdk::config::{FeeConfig, OracleConfig};
-    use near_sdk::{json_types::U64, testing_env, MockedBlockchain, VMContext};
+    use crate::test_utils::*;
+    use flux_sdk::config::OracleConfig;
+    use near_sdk::{testing_env, MockedBlockchain, VMContext};
     use std::convert::TryInto;
 
-    fn alice() -> AccountId {
-        "alice.near".to_string()
-    }
-
-    fn bob() -> AccountId {
-        "bob.near".to_string()
-    }
-
-    fn carol() -> AccountId {
-        "carol.near".to_string()
-    }
-
-    fn token() -> AccountId {
-        "token.near".to_string()
-    }
-
     fn _target() -> AccountId {
         "target.near".to_string()
     }
 
-    fn gov() -> AccountId {
-        "gov.near".to_string()
-    }
-
     fn to_valid(account: AccountId) -> ValidAccountId {
         account.try_into().expect("invalid account")
     }
 
-    fn registry_entry(account: AccountId) -> Requester {
-        Requester {
-            contract_name: account.clone(),
-            account_id: account.clone(),
-            stake_multiplier: None,
-            code_base_url: None,
-        }
-    }
-
     fn config() -> OracleConfig {
         OracleConfig {
-            gov: gov(),
-            final_arbitrator: alice(),
-            payment_token: token(),
-            stake_token: token(),
-            validity_bond: U128(100),
-            max_outcomes: 8,
-            default_challenge_window_duration: U64(1000),
-            min_initial_challenge_window_duration: U64(1000),
-            final_arbitrator_invoke_amount: U128(250),
-            fee: FeeConfig {
-                flux_market_cap: U128(50000),
-                total_value_staked: U128(10000),
-                resolution_fee_percentage: 5000, // 5%
-            },
-            min_resolution_bond: U128(100),
-        }
-    }
-
-    fn get_context(predecessor_account_id: AccountId) -> VMContext {
-        VMContext {
-            current_account_id: token(),
-            signer_account_id: bob(),
-            signer_account_pk: vec![0, 1, 2],
-            predecessor_account_id,
-            input: vec![],
-            block_index: 0,
-            block_timestamp: 0,
-            account_balance: 1000 * 10u128.pow(24),
-            account_locked_balance: 0,
-            storage_usage: 10u64.pow(6),
-            attached_deposit: 1000 * 10u128.pow(24),
-            prepaid_gas: 10u64.pow(18),
-            random_seed: vec![0, 1, 2],
-            is_view: false,
-            output_data_receivers: vec![],
-            epoch_height: 0,
+            fee: fee_config(5000), // 5%
+            ..new_config(250)
         }
     }
 
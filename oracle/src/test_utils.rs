@@ -0,0 +1,108 @@
+use flux_sdk::{
+    config::{FeeConfig, OracleConfig},
+    requester::Requester,
+};
+use near_sdk::{
+    json_types::{U128, U64},
+    AccountId, VMContext,
+};
+
+pub fn alice() -> AccountId {
+    "alice.near".to_string()
+}
+
+pub fn bob() -> AccountId {
+    "bob.near".to_string()
+}
+
+pub fn carol() -> AccountId {
+    "carol.near".to_string()
+}
+
+pub fn dave() -> AccountId {
+    "dave.near".to_string()
+}
+
+pub fn token() -> AccountId {
+    "token.near".to_string()
+}
+
+pub fn gov() -> AccountId {
+    "gov.near".to_string()
+}
+
+pub fn registry_entry(account: AccountId) -> Requester {
+    Requester {
+        contract_name: account.clone(),
+        account_id: account.clone(),
+        stake_multiplier: None,
+        code_base_url: None,
+    }
+}
+
+pub fn fee_config(resolution_fee_percentage: u32) -> FeeConfig {
+    FeeConfig {
+        flux_market_cap: U128(50000),
+        total_value_staked: U128(10000),
+        resolution_fee_percentage,
+    }
+}
+
+pub fn new_config(final_arb_amount: u128) -> OracleConfig {
+    OracleConfig {
+        gov: gov(),
+        final_arbitrator: alice(),
+        payment_token: token(),
+        stake_token: token(),
+        validity_bond: U128(100),
+        max_outcomes: 8,
+        default_challenge_window_duration: U64(1000),
+        min_initial_challenge_window_duration: U64(1000),
+        final_arbitrator_invoke_amount: U128(final_arb_amount),
+        fee: fee_config(10_000),
+        min_resolution_bond: U128(100),
+    }
+}
+
+pub fn config() -> OracleConfig {
+    new_config(1_000_000)
+}
+
+pub fn without_fee(mut config: OracleConfig) -> OracleConfig {
+    config.fee.resolution_fee_percentage = 0;
+    config
+}
+
+// requests in these fixtures can be created by paying just the validity bond
+pub fn zero_fee_config() -> OracleConfig {
+    without_fee(config())
+}
+
+pub fn zero_fee_config_for_quick_final_arb() -> OracleConfig {
+    without_fee(new_config(250))
+}
+
+pub fn get_context(predecessor_account_id: AccountId) -> VMContext {
+    get_context_at(predecessor_account_id, 0)
+}
+
+pub fn get_context_at(predecessor_account_id: AccountId, block_timestamp: u64) -> VMContext {
+    VMContext {
+        current_account_id: token(),
+        signer_account_id: bob(),
+        signer_account_pk: vec![0, 1, 2],
+        predecessor_account_id,
+        input: vec![],
+        block_index: 0,
+        block_timestamp,
+        account_balance: 10000 * 10u128.pow(24),
+        account_locked_balance: 0,
+        storage_usage: 10u64.pow(6),
+        attached_deposit: 1000 * 10u128.pow(24),
+        prepaid_gas: 10u64.pow(18),
+        random_seed: vec![0, 1, 2],
+        is_view: false,
+        output_data_receivers: vec![],
+        epoch_height: 0,
+    }
+}
@@ -57,12 +57,47 @@ mod upgrade {
     }
 }
 
+/// Contract state as deployed before the current version
+#[derive(BorshDeserialize)]
+pub struct OldContract {
+    pub whitelist: whitelist::Whitelist,
+    pub configs: Vector<OracleConfig>,
+    pub data_requests: Vector<DataRequest>,
+    pub accounts: LookupMap<AccountId, AccountStorageBalance>,
+    pub paused: bool,
+}
+
 #[near_bindgen]
 impl Contract {
     // AUDIT: Mark it `#[private]`
     #[init(ignore_state)]
     pub fn migrate() -> Self {
-        let contract: Contract = env::state_read().expect("ERR_NOT_INITIALIZED");
-        contract
+        let contract: OldContract = env::state_read().expect("ERR_NOT_INITIALIZED");
+        Self {
+            whitelist: contract.whitelist,
+            configs: contract.configs,
+            data_requests: contract.data_requests,
+            accounts: contract.accounts,
+            paused: contract.paused,
+            failed_payouts: LookupMap::new(b"fp".to_vec()),
+            extended_config: oracle_config::ExtendedOracleConfig::default(),
+            set_outcome_gas: LookupMap::new(b"sog".to_vec()),
+            deliveries: LookupMap::new(b"dl".to_vec()),
+            failed_deliveries: UnorderedSet::new(b"fd".to_vec()),
+            series: Vector::new(b"srs".to_vec()),
+            cancelled_requests: LookupSet::new(b"cdr".to_vec()),
+            request_expiries: LookupMap::new(b"exp".to_vec()),
+            account_stakes: LookupMap::new(b"as".to_vec()),
+            total_bonded: LookupMap::new(b"tb".to_vec()),
+            fee_top_ups: LookupMap::new(b"fto".to_vec()),
+            fee_top_up_senders: LookupMap::new(b"fts".to_vec()),
+            bond_settings: LookupMap::new(b"bs".to_vec()),
+            numeric_bounds: LookupMap::new(b"nb".to_vec()),
+            round_numbers: LookupMap::new(b"rn".to_vec()),
+            answer_formats: LookupMap::new(b"af".to_vec()),
+            revealed_payloads: LookupMap::new(b"rvp".to_vec()),
+            commit_rounds: LookupMap::new(b"cmr".to_vec()),
+            provider_quorums: LookupMap::new(b"pq".to_vec()),
+        }
     }
 }
@@ -1,12 +1,21 @@
+use crate::outcome_delivery::GAS_BASE_RESOLVE_DELIVERY;
 use crate::requester_handler::RequesterHandler;
 use crate::*;
-use flux_sdk::{config::OracleStorageKey, requester::Requester};
+use flux_sdk::{
+    config::OracleStorageKey,
+    consts::{GAS_BASE_SET_OUTCOME, GAS_BASE_TRANSFER},
+    requester::Requester,
+};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     collections::LookupMap,
-    AccountId,
+    json_types::U64,
+    AccountId, Gas,
 };
 
+/// Gas attached to `set_outcome` for requesters that did not set their own
+pub const DEFAULT_SET_OUTCOME_GAS: Gas = GAS_BASE_SET_OUTCOME / 10;
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Whitelist(Option<LookupMap<AccountId, Requester>>); // maps requester account id to requesters config
 
@@ -110,7 +119,67 @@ impl WhitelistHandler for Contract {
     }
 }
 
+#[near_bindgen]
+impl Contract {
+    /**
+     * @notice Sets the gas `requester` wants attached to the `set_outcome` call it receives on finalization,
+     * callable by governance or by the requester itself if it's whitelisted
+     */
+    #[payable]
+    pub fn set_requester_set_outcome_gas(&mut self, requester: AccountId, gas: U64) {
+        let sender = env::predecessor_account_id();
+        if sender == requester {
+            self.assert_whitelisted(requester.to_string());
+        } else {
+            self.assert_gov();
+        }
+        let gas: Gas = gas.into();
+        let max_gas: Gas = self.extended_config.max_set_outcome_gas.into();
+        assert!(
+            gas <= max_gas,
+            "set_outcome gas can't be higher than {}",
+            max_gas
+        );
+
+        let initial_storage = env::storage_usage();
+        self.set_outcome_gas.insert(&requester, &gas);
+        logger::log_set_outcome_gas(&requester, gas);
+        helpers::refund_storage(initial_storage, sender);
+    }
+
+    // @returns the gas attached to the `set_outcome` call `requester` receives
+    pub fn get_requester_set_outcome_gas(&self, requester: AccountId) -> U64 {
+        let gas = self
+            .set_outcome_gas
+            .get(&requester)
+            .unwrap_or(DEFAULT_SET_OUTCOME_GAS);
+        // the cap can be lowered after the requester set its gas
+        U64(std::cmp::min(
+            gas,
+            self.extended_config.max_set_outcome_gas.into(),
+        ))
+    }
+}
+
 impl Contract {
+    /**
+     * @notice Asserts enough prepaid gas remains for `set_outcome` on `requester`, the callback recording its delivery
+     * and `other_gas`, the gas of every other promise the caller schedules (e.g. token transfers and their callbacks)
+     * @returns the gas to attach to `set_outcome`
+     */
+    pub fn assert_set_outcome_gas(&self, requester: &AccountId, other_gas: Gas) -> Gas {
+        let gas: Gas = self
+            .get_requester_set_outcome_gas(requester.to_string())
+            .into();
+        let required = gas + GAS_BASE_RESOLVE_DELIVERY + other_gas;
+        assert!(
+            env::prepaid_gas() - env::used_gas() >= required,
+            "not enough gas to call set_outcome on the requester, {} is required",
+            required
+        );
+        gas
+    }
+
     pub fn assert_whitelisted(&self, requester: AccountId) {
         match self.whitelist.0 {
             Some(_) => {
@@ -128,76 +197,14 @@ impl Contract {
 #[cfg(test)]
 mod mock_token_basic_tests {
     use super::*;
-    use flux_sdk::config::FeeConfig;
-    use near_sdk::{json_types::U64, testing_env, MockedBlockchain, VMContext};
-
-    fn alice() -> AccountId {
-        "alice.near".to_string()
-    }
-
-    fn bob() -> AccountId {
-        "bob.near".to_string()
-    }
-
-    fn carol() -> AccountId {
-        "carol.near".to_string()
-    }
-
-    fn token() -> AccountId {
-        "token.near".to_string()
-    }
-
-    fn gov() -> AccountId {
-        "gov.near".to_string()
-    }
-
-    fn registry_entry(account: AccountId) -> Requester {
-        Requester {
-            contract_name: account.clone(),
-            account_id: account.clone(),
-            stake_multiplier: None,
-            code_base_url: None,
-        }
-    }
+    use crate::test_utils::*;
+    use near_sdk::{testing_env, MockedBlockchain};
 
     fn config() -> OracleConfig {
         OracleConfig {
-            gov: gov(),
-            final_arbitrator: alice(),
-            payment_token: token(),
-            stake_token: token(),
             validity_bond: U128(0),
-            max_outcomes: 8,
-            default_challenge_window_duration: U64(1000),
-            min_initial_challenge_window_duration: U64(1000),
-            final_arbitrator_invoke_amount: U128(25_000_000_000_000_000_000_000_000_000_000),
-            fee: FeeConfig {
-                flux_market_cap: U128(50000),
-                total_value_staked: U128(10000),
-                resolution_fee_percentage: 5000, // 5%
-            },
-            min_resolution_bond: U128(100),
-        }
-    }
-
-    fn get_context(predecessor_account_id: AccountId) -> VMContext {
-        VMContext {
-            current_account_id: token(),
-            signer_account_id: bob(),
-            signer_account_pk: vec![0, 1, 2],
-            predecessor_account_id,
-            input: vec![],
-            block_index: 0,
-            block_timestamp: 0,
-            account_balance: 1000 * 10u128.pow(24),
-            account_locked_balance: 0,
-            storage_usage: 10u64.pow(6),
-            attached_deposit: 1000 * 10u128.pow(24),
-            prepaid_gas: 10u64.pow(18),
-            random_seed: vec![0, 1, 2],
-            is_view: false,
-            output_data_receivers: vec![],
-            epoch_height: 0,
+            fee: fee_config(5000), // 5%
+            ..new_config(25_000_000_000_000_000_000_000_000_000_000)
         }
     }
 
@@ -252,4 +259,114 @@ mod mock_token_basic_tests {
         let mut contract = Contract::new(whitelist, config());
         contract.remove_from_whitelist(registry_entry(alice()));
     }
+
+    #[test]
+    fn set_outcome_gas_by_gov_and_requester() {
+        testing_env!(get_context(gov()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        assert_eq!(
+            contract.get_requester_set_outcome_gas(bob()),
+            U64(DEFAULT_SET_OUTCOME_GAS)
+        );
+
+        contract.set_requester_set_outcome_gas(bob(), U64(50_000_000_000_000));
+        assert_eq!(
+            contract.get_requester_set_outcome_gas(bob()),
+            U64(50_000_000_000_000)
+        );
+
+        testing_env!(get_context(carol()));
+        contract.set_requester_set_outcome_gas(carol(), U64(GAS_BASE_SET_OUTCOME));
+        assert_eq!(
+            contract.get_requester_set_outcome_gas(carol()),
+            U64(GAS_BASE_SET_OUTCOME)
+        );
+
+        // lowering the cap lowers the gas of requesters above it
+        testing_env!(get_context(gov()));
+        contract.set_extended_config(oracle_config::ExtendedOracleConfig {
+            max_set_outcome_gas: U64(10_000_000_000_000),
+            ..oracle_config::ExtendedOracleConfig::default()
+        });
+        assert_eq!(
+            contract.get_requester_set_outcome_gas(carol()),
+            U64(10_000_000_000_000)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "This method is only callable by the governance contract gov.near")]
+    fn set_outcome_gas_of_other_requester() {
+        testing_env!(get_context(carol()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.set_requester_set_outcome_gas(bob(), U64(50_000_000_000_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "set_outcome gas can't be higher than 250000000000000")]
+    fn set_outcome_gas_above_cap() {
+        testing_env!(get_context(bob()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.set_requester_set_outcome_gas(bob(), U64(GAS_BASE_SET_OUTCOME + 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Err predecessor is not whitelisted")]
+    fn set_outcome_gas_by_unlisted_requester() {
+        testing_env!(get_context(alice()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let mut contract = Contract::new(whitelist, config());
+        contract.set_requester_set_outcome_gas(alice(), U64(50_000_000_000_000));
+    }
+
+    #[test]
+    fn set_outcome_gas_reserves_delivery_callback() {
+        testing_env!(get_context(gov()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let contract = Contract::new(whitelist, config());
+        let required = DEFAULT_SET_OUTCOME_GAS + GAS_BASE_RESOLVE_DELIVERY + GAS_BASE_TRANSFER;
+
+        let mut context = get_context(gov());
+        // leaves room for the gas used by reading the requester's gas
+        context.prepaid_gas = required + 1_000_000_000_000;
+        testing_env!(context);
+        assert_eq!(
+            contract.assert_set_outcome_gas(&bob(), GAS_BASE_TRANSFER),
+            DEFAULT_SET_OUTCOME_GAS
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough gas to call set_outcome on the requester")]
+    fn set_outcome_gas_without_delivery_callback() {
+        testing_env!(get_context(gov()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let contract = Contract::new(whitelist, config());
+
+        // one short of what set_outcome, the callback recording its delivery and the token transfer need
+        let mut context = get_context(gov());
+        context.prepaid_gas =
+            DEFAULT_SET_OUTCOME_GAS + GAS_BASE_TRANSFER + GAS_BASE_RESOLVE_DELIVERY - 1;
+        testing_env!(context);
+        contract.assert_set_outcome_gas(&bob(), GAS_BASE_TRANSFER);
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough gas to call set_outcome on the requester")]
+    fn set_outcome_gas_without_payouts() {
+        testing_env!(get_context(gov()));
+        let whitelist = Some(vec![registry_entry(bob()), registry_entry(carol())]);
+        let contract = Contract::new(whitelist, config());
+
+        // enough for set_outcome and a token transfer, but not for a payout and its callback on top
+        let mut context = get_context(gov());
+        context.prepaid_gas =
+            DEFAULT_SET_OUTCOME_GAS + GAS_BASE_TRANSFER + GAS_BASE_RESOLVE_DELIVERY;
+        testing_env!(context);
+        contract
+            .assert_set_outcome_gas(&bob(), GAS_BASE_TRANSFER + failed_payouts::GAS_BASE_PAYOUT);
+    }
 }
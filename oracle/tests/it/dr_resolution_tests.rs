@@ -10,6 +10,7 @@ fn dr_claim_flow() {
     let stake_cost = 200;
     let validity_bond = 1;
     let fee = 5;
+    let resolution_fee = 0; // 5% of a validity bond of 1 rounds down to 0, the rest gets refunded
 
     let init_res = TestUtils::init(None);
     let init_balance_alice = init_res.alice.get_token_balance(None);
@@ -19,7 +20,7 @@ fn dr_claim_flow() {
         .alice
         .get_token_balance(Some(ORACLE_CONTRACT_ID.to_string()));
 
-    assert_eq!(post_new_balance_oracle, validity_bond + fee);
+    assert_eq!(post_new_balance_oracle, validity_bond + resolution_fee);
 
     let dr_exist = init_res.alice.dr_exists(0);
     assert!(dr_exist, "something went wrong during dr creation");
@@ -41,28 +42,62 @@ fn dr_claim_flow() {
     init_res.alice.claim(0);
 
     let post_claim_balance_alice = init_res.alice.get_token_balance(None);
-    assert_eq!(post_claim_balance_alice, init_balance_alice - validity_bond); // expect initial balance - validity bond since refunds are handled by requester contract
+    assert_eq!(
+        post_claim_balance_alice,
+        init_balance_alice - validity_bond - fee
+    ); // expect initial balance - validity bond - fee since refunds are handled by requester contract
+}
+
+#[test]
+fn dr_claim_many_flow() {
+    let stake_amount = to_yocto("250");
+    let validity_bond = 1;
+    let fee = 5;
+
+    let init_res = TestUtils::init(None);
+    let init_balance_alice = init_res.alice.get_token_balance(None);
+
+    let _res = init_res.alice.dr_new(fee, None);
+    let _res = init_res.alice.dr_new(fee, None);
+    let outcome = Outcome::Answer(AnswerType::String("test".to_string()));
+    let _res = init_res.alice.stake(0, outcome.clone(), stake_amount);
+    let _res = init_res.alice.stake(1, outcome, stake_amount);
+
+    init_res.bob.ft_transfer(&REQUESTER_CONTRACT_ID, 1_000_000);
+    init_res.alice.finalize(0);
+    init_res.alice.finalize(1);
+    init_res.alice.claim_many(vec![0, 1]);
+
+    let post_claim_balance_alice = init_res.alice.get_token_balance(None);
+    assert_eq!(
+        post_claim_balance_alice,
+        init_balance_alice - 2 * (validity_bond + fee)
+    );
 }
 
 #[test]
 fn dr_fixed_fee_flow() {
     let custom_fee_amount = 100;
     let stake_amount = to_yocto("250");
-    let dr_cost = 1 + custom_fee_amount;
+    let validity_bond = 1000;
+    // 5% of the validity bond scaled by (50000 - 10000) / 50000, the rest of the custom fee is refunded to the requester
+    let resolution_fee = 40;
+    let dr_cost = validity_bond + custom_fee_amount;
 
     let init_res = TestUtils::init(Some(TestSetupArgs {
         stake_multiplier: None,
-        validity_bond: 1,
+        validity_bond,
         min_resolution_bond: 1,
         final_arbitrator_invoke_amount: 2500,
     }));
 
     let init_balance_alice = init_res.alice.get_token_balance(None);
 
-    let _res = init_res.alice.dr_new(custom_fee_amount, Some(1));
-    let _post_new_balance_oracle = init_res
+    let _res = init_res.alice.dr_new(custom_fee_amount, Some(validity_bond));
+    let post_new_balance_oracle = init_res
         .alice
         .get_token_balance(Some(ORACLE_CONTRACT_ID.to_string()));
+    assert_eq!(post_new_balance_oracle, validity_bond + resolution_fee);
 
     let dr_exist = init_res.alice.dr_exists(0);
     assert!(dr_exist, "something went wrong during dr creation");
@@ -77,7 +112,7 @@ fn dr_fixed_fee_flow() {
     let post_stake_balance_alice = init_res.alice.get_token_balance(None);
     assert_eq!(
         post_stake_balance_alice,
-        init_balance_alice - dr_cost - custom_fee_amount * 2
+        init_balance_alice - dr_cost - validity_bond * 2
     );
 
     init_res.bob.ft_transfer(&REQUESTER_CONTRACT_ID, 100_000);
@@ -86,24 +121,29 @@ fn dr_fixed_fee_flow() {
     init_res.alice.claim(0);
 
     let post_claim_balance_alice = init_res.alice.get_token_balance(None);
-    assert_eq!(post_claim_balance_alice, init_balance_alice - 1); // expect initial balance - validity bond since refunds are handled by requester contract
+    assert_eq!(
+        post_claim_balance_alice,
+        init_balance_alice - dr_cost + resolution_fee
+    ); // expect initial balance - request cost + resolution fee since refunds are handled by requester contract
 }
 
 #[test]
 fn dr_multiplier_flow() {
-    let stake_cost = 200;
+    let validity_bond = 1000;
+    let stake_cost = validity_bond * 2;
     let multiplier_amount = 10500_u16; // 105%
     let stake_amount = to_yocto("250");
-    let dr_cost = 101;
+    let resolution_fee = 40;
+    let dr_cost = validity_bond + 100;
     let init_res = TestUtils::init(Some(TestSetupArgs {
         stake_multiplier: Some(multiplier_amount),
-        validity_bond: 1,
+        validity_bond,
         min_resolution_bond: 1,
         final_arbitrator_invoke_amount: 2500,
     }));
     let init_balance_alice = init_res.alice.get_token_balance(None);
 
-    let _res = init_res.alice.dr_new(100, Some(1));
+    let _res = init_res.alice.dr_new(100, Some(validity_bond));
     let _post_new_balance_oracle = init_res
         .alice
         .get_token_balance(Some(ORACLE_CONTRACT_ID.to_string()));
@@ -130,5 +170,8 @@ fn dr_multiplier_flow() {
     init_res.alice.claim(0);
 
     let post_claim_balance_alice = init_res.alice.get_token_balance(None);
-    assert_eq!(post_claim_balance_alice, init_balance_alice - 1); // expect initial balance - validity bond since refunds are handled by requester contract
+    assert_eq!(
+        post_claim_balance_alice,
+        init_balance_alice - dr_cost + resolution_fee
+    ); // expect initial balance - request cost + resolution fee since refunds are handled by requester contract
 }
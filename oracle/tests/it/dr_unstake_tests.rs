@@ -0,0 +1,37 @@
+use crate::utils::*;
+use flux_sdk::outcome::{AnswerType, Outcome};
+
+#[test]
+fn dr_unstake_flow() {
+    let init_res = TestUtils::init(None);
+    let _new_res = init_res.alice.dr_new(0, None);
+    let outcome = Outcome::Answer(AnswerType::String("test".to_string()));
+
+    let init_balance_alice = init_res.alice.get_token_balance(None);
+    let init_balance_bob = init_res.bob.get_token_balance(None);
+
+    // bond size is 200, so neither stake bonds the outcome
+    init_res.alice.stake(0, outcome.clone(), 50);
+    init_res.bob.stake(0, outcome.clone(), 30);
+    assert_eq!(init_res.alice.get_outcome_stake(0, 0, outcome.clone()), 80);
+
+    init_res.alice.unstake(0, 0, outcome.clone(), 10);
+    init_res.alice.unstake(0, 0, outcome.clone(), 15);
+    init_res.bob.unstake(0, 0, outcome.clone(), 30);
+
+    assert_eq!(
+        init_res.alice.get_user_outcome_stake(0, 0, outcome.clone()),
+        25
+    );
+    assert_eq!(
+        init_res.bob.get_user_outcome_stake(0, 0, outcome.clone()),
+        0
+    );
+    assert_eq!(init_res.alice.get_outcome_stake(0, 0, outcome), 25);
+
+    assert_eq!(
+        init_res.alice.get_token_balance(None),
+        init_balance_alice - 25
+    );
+    assert_eq!(init_res.bob.get_token_balance(None), init_balance_bob);
+}
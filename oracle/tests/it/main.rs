@@ -4,4 +4,5 @@ mod utils;
 mod dr_basic_tests;
 mod dr_resolution_tests;
 mod dr_scenario_tests;
+mod dr_unstake_tests;
 mod init;
@@ -87,6 +87,45 @@ impl TestAccount {
             .unwrap_json()
     }
 
+    pub fn get_outcome_stake(&self, dr_id: u64, round: u16, outcome: Outcome) -> u128 {
+        let res: U128 = self
+            .account
+            .view(
+                ORACLE_CONTRACT_ID.to_string(),
+                "get_outcome_stake",
+                json!({
+                    "request_id": U64(dr_id),
+                    "resolution_round": round,
+                    "outcome": outcome
+                })
+                .to_string()
+                .as_bytes(),
+            )
+            .unwrap_json();
+
+        res.into()
+    }
+
+    pub fn get_user_outcome_stake(&self, dr_id: u64, round: u16, outcome: Outcome) -> u128 {
+        let res: U128 = self
+            .account
+            .view(
+                ORACLE_CONTRACT_ID.to_string(),
+                "get_user_outcome_stake",
+                json!({
+                    "request_id": U64(dr_id),
+                    "resolution_round": round,
+                    "account_id": self.account.account_id(),
+                    "outcome": outcome
+                })
+                .to_string()
+                .as_bytes(),
+            )
+            .unwrap_json();
+
+        res.into()
+    }
+
     /*** Setters ***/
     pub fn dr_new(&self, fee: u128, custom_validity_bond: Option<u128>) -> ExecutionResult {
         // Transfer validity bond to to the request interface contract, this way it has balance to pay for the DataRequest creation
@@ -150,6 +189,32 @@ impl TestAccount {
         res
     }
 
+    pub fn unstake(
+        &self,
+        dr_id: u64,
+        round: u16,
+        outcome: Outcome,
+        amount: u128,
+    ) -> ExecutionResult {
+        let res = self.account.call(
+            ORACLE_CONTRACT_ID.to_string(),
+            "dr_unstake",
+            json!({
+                "request_id": U64(dr_id),
+                "resolution_round": round,
+                "outcome": outcome,
+                "amount": U128(amount)
+            })
+            .to_string()
+            .as_bytes(),
+            MAX_GAS,
+            0,
+        );
+
+        res.assert_success();
+        res
+    }
+
     pub fn finalize(&self, dr_id: u64) -> ExecutionResult {
         let res = self.account.call(
             ORACLE_CONTRACT_ID.to_string(),
@@ -199,6 +264,25 @@ impl TestAccount {
         res
     }
 
+    pub fn claim_many(&self, dr_ids: Vec<u64>) -> ExecutionResult {
+        let request_ids: Vec<U64> = dr_ids.into_iter().map(U64).collect();
+        let res = self.account.call(
+            ORACLE_CONTRACT_ID.to_string(),
+            "dr_claim_many",
+            json!({
+                "account_id": self.account.account_id(),
+                "request_ids": request_ids
+            })
+            .to_string()
+            .as_bytes(),
+            MAX_GAS,
+            1000000000000000000000,
+        );
+
+        res.assert_success();
+        res
+    }
+
     fn ft_transfer_call(&self, receiver: &str, amount: u128, msg: String) -> ExecutionResult {
         let res = self.account.call(
             TOKEN_CONTRACT_ID.to_string(),
@@ -20,7 +20,8 @@ impl RequesterContractUtils {
             init_method: new(
                 ORACLE_CONTRACT_ID.to_string(),
                 TOKEN_CONTRACT_ID.to_string(),
-                None
+                // only alice creates data requests in these tests
+                Some(vec!["alice".to_string()])
             )
         );
 
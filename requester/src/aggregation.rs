@@ -0,0 +1,415 @@
+use crate::consumer::{decode_outcome, FixedPoint, TypedOutcome};
+use crate::*;
+use flux_sdk::data_request::DataRequestDataType;
+use near_sdk::serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Data requests asking the same question, e.g. one per source, of which the outcomes are combined into one value
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RequestGroup {
+    pub id: U64,
+    pub creator: AccountId,
+    pub tag: String, // added to the tags of every member
+    pub size: u16,
+    pub quorum: u16,      // valid answers required before the group has a result
+    pub max_invalid: u16, // members that may resolve to Invalid before the whole group is Invalid
+    pub data_type: DataRequestDataType,
+    pub outcomes: Option<Vec<String>>,
+    pub members: Vec<U64>,
+    pub answers: Vec<TypedOutcome>,
+    pub invalid_count: u16,
+    // median of numbers or majority of strings, `Invalid` if too many members were. Final once set
+    pub result: Option<TypedOutcome>,
+}
+
+impl RequestGroup {
+    fn aggregate(&self) -> Option<TypedOutcome> {
+        if self.invalid_count > self.max_invalid {
+            return Some(TypedOutcome::Invalid);
+        }
+        if (self.answers.len() as u16) < self.quorum {
+            return None;
+        }
+
+        match self.data_type {
+            DataRequestDataType::Number(_) => median(&self.answers),
+            DataRequestDataType::String => majority(&self.answers),
+        }
+    }
+}
+
+fn cmp_fixed_point(a: &FixedPoint, b: &FixedPoint) -> Ordering {
+    match (a.negative, b.negative) {
+        (false, false) => a.value.0.cmp(&b.value.0),
+        (true, true) => b.value.0.cmp(&a.value.0),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    }
+}
+
+// @returns the median of the number answers, the lower of the two middle answers for an even count
+fn median(answers: &[TypedOutcome]) -> Option<TypedOutcome> {
+    let mut numbers: Vec<FixedPoint> = answers
+        .iter()
+        .filter_map(|answer| match answer {
+            TypedOutcome::Number(number) => Some(*number),
+            _ => None,
+        })
+        .collect();
+    if numbers.is_empty() {
+        return None;
+    }
+    numbers.sort_by(cmp_fixed_point);
+    Some(TypedOutcome::Number(numbers[(numbers.len() - 1) / 2]))
+}
+
+// @returns the most given answer, on a tie the one that reached the count first
+fn majority(answers: &[TypedOutcome]) -> Option<TypedOutcome> {
+    let mut counts: Vec<(&TypedOutcome, u16)> = Vec::new();
+    for answer in answers {
+        match counts.iter_mut().find(|(a, _)| *a == answer) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((answer, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .fold(None, |best: Option<(&TypedOutcome, u16)>, (answer, count)| match best {
+            Some((_, best_count)) if best_count >= count => best,
+            _ => Some((answer, count)),
+        })
+        .map(|(answer, _)| answer.clone())
+}
+
+#[near_bindgen]
+impl RequesterContract {
+    /**
+     * @notice Opens a group of `size` data requests of which the outcomes are aggregated once `quorum` valid answers are in.
+     * Members are added by the creator through `create_data_request` with the returned group id
+     * @returns the id of the group
+     */
+    pub fn create_request_group(
+        &mut self,
+        tag: String,
+        size: u16,
+        quorum: u16,
+        max_invalid: u16,
+        data_type: DataRequestDataType,
+        outcomes: Option<Vec<String>>,
+    ) -> U64 {
+        let creator = env::predecessor_account_id();
+        self.assert_whitelisted(&creator);
+        assert!(
+            quorum > 0 && quorum <= size,
+            "quorum has to be between 1 and the group size"
+        );
+        assert!(
+            max_invalid < size,
+            "max invalid has to be lower than the group size"
+        );
+
+        let id = self.group_nonce;
+        self.group_nonce += 1;
+        self.groups.insert(
+            &id,
+            &RequestGroup {
+                id: U64(id),
+                creator,
+                tag,
+                size,
+                quorum,
+                max_invalid,
+                data_type,
+                outcomes,
+                members: Vec::new(),
+                answers: Vec::new(),
+                invalid_count: 0,
+                result: None,
+            },
+        );
+
+        U64(id)
+    }
+
+    pub fn get_request_group(&self, group_id: U64) -> Option<RequestGroup> {
+        self.groups.get(&group_id.into())
+    }
+
+    pub fn get_group_result(&self, group_id: U64) -> Option<TypedOutcome> {
+        self.groups
+            .get(&group_id.into())
+            .and_then(|group| group.result)
+    }
+}
+
+impl RequesterContract {
+    /**
+     * @notice Adds a data request to a group, asserting it asks the same kind of question
+     * @returns the group tag to add to the data request's tags
+     */
+    pub fn add_group_member(
+        &mut self,
+        group_id: U64,
+        nonce: u64,
+        creator: &AccountId,
+        data_type: &DataRequestDataType,
+        outcomes: &Option<Vec<String>>,
+    ) -> String {
+        let mut group = self
+            .groups
+            .get(&group_id.into())
+            .expect("ERR_UNKNOWN_GROUP");
+        assert_eq!(
+            &group.creator, creator,
+            "only the group creator can add data requests"
+        );
+        assert!(
+            (group.members.len() as u16) < group.size,
+            "group is already full"
+        );
+        assert_eq!(&group.data_type, data_type, "ERR_WRONG_DATA_TYPE");
+        assert_eq!(&group.outcomes, outcomes, "ERR_WRONG_OUTCOMES");
+
+        group.members.push(U64(nonce));
+        self.groups.insert(&group_id.into(), &group);
+        group.tag
+    }
+
+    // frees the place of a member of which the creation failed
+    pub fn remove_group_member(&mut self, group_id: U64, nonce: u64) {
+        let mut group = self.groups.get(&group_id.into()).unwrap();
+        group.members.retain(|member| u64::from(*member) != nonce);
+        self.groups.insert(&group_id.into(), &group);
+    }
+
+    /**
+     * @notice Adds the outcome of a member to its group and sets the group's result once there is one. The result is
+     * frozen from then on, members resolving later are recorded but don't change it, so consumers that acted on the
+     * result can rely on it. An outcome that doesn't match the group's data type is counted as Invalid rather than
+     * panicking, which would fail the oracle's delivery
+     */
+    pub fn record_group_outcome(&mut self, group_id: U64, outcome: &Outcome) {
+        let mut group = self.groups.get(&group_id.into()).unwrap();
+        match decode_outcome(outcome, &group.data_type, group.outcomes.as_ref()) {
+            Ok(TypedOutcome::Invalid) | Err(_) => group.invalid_count += 1,
+            Ok(answer) => group.answers.push(answer),
+        }
+
+        if group.result.is_none() {
+            group.result = group.aggregate();
+        }
+        self.groups.insert(&group_id.into(), &group);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use flux_sdk::{
+        data_request::NewDataRequestArgs,
+        outcome::{AnswerNumberType, AnswerType},
+    };
+    use near_sdk::{json_types::U128, testing_env, MockedBlockchain, VMContext};
+
+    fn alice() -> AccountId {
+        "alice.near".to_string()
+    }
+
+    fn bob() -> AccountId {
+        "bob.near".to_string()
+    }
+
+    fn oracle() -> AccountId {
+        "oracle.near".to_string()
+    }
+
+    fn token() -> AccountId {
+        "token.near".to_string()
+    }
+
+    fn requester() -> AccountId {
+        "requester.near".to_string()
+    }
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContext {
+        VMContext {
+            current_account_id: requester(),
+            signer_account_id: alice(),
+            signer_account_pk: vec![0, 1, 2],
+            predecessor_account_id,
+            input: vec![],
+            block_index: 0,
+            block_timestamp: 0,
+            account_balance: 10000 * 10u128.pow(24),
+            account_locked_balance: 0,
+            storage_usage: 10u64.pow(6),
+            attached_deposit: 0,
+            prepaid_gas: 10u64.pow(18),
+            random_seed: vec![0, 1, 2],
+            is_view: false,
+            output_data_receivers: vec![],
+            epoch_height: 0,
+        }
+    }
+
+    fn payload(data_type: DataRequestDataType) -> NewDataRequestArgs {
+        NewDataRequestArgs {
+            sources: Some(Vec::new()),
+            outcomes: None,
+            challenge_period: U64(1000),
+            description: Some("a".to_string()),
+            tags: vec!["price".to_string()],
+            data_type,
+            provider: None,
+        }
+    }
+
+    fn number(value: u128, negative: bool) -> Outcome {
+        Outcome::Answer(AnswerType::Number(AnswerNumberType {
+            value: U128(value),
+            multiplier: U128(100),
+            negative,
+        }))
+    }
+
+    fn string(value: &str) -> Outcome {
+        Outcome::Answer(AnswerType::String(value.to_string()))
+    }
+
+    fn group_contract(
+        size: u16,
+        quorum: u16,
+        max_invalid: u16,
+        data_type: DataRequestDataType,
+    ) -> RequesterContract {
+        testing_env!(get_context(alice()));
+        let mut contract = RequesterContract::new(oracle(), token(), Some(vec![alice(), bob()]));
+        let group_id = contract.create_request_group(
+            "group".to_string(),
+            size,
+            quorum,
+            max_invalid,
+            data_type.clone(),
+            None,
+        );
+        for _ in 0..size {
+            contract.create_data_request(U128(100), payload(data_type.clone()), Some(group_id));
+        }
+        testing_env!(get_context(oracle()));
+        contract
+    }
+
+    fn set_outcome(contract: &mut RequesterContract, nonce: u64, outcome: Outcome) {
+        let validity_bond_returned = outcome != Outcome::Invalid;
+        contract.set_outcome(
+            requester(),
+            outcome,
+            vec!["price".to_string(), "group".to_string(), nonce.to_string()],
+            validity_bond_returned,
+        );
+    }
+
+    #[test]
+    fn group_members() {
+        let contract = group_contract(2, 1, 0, DataRequestDataType::String);
+        let group = contract.get_request_group(U64(0)).unwrap();
+        assert_eq!(group.members, vec![U64(0), U64(1)]);
+        assert_eq!(
+            contract.get_data_request(U64(1)).unwrap().tags,
+            vec!["price".to_string(), "group".to_string()]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "group is already full")]
+    fn group_full() {
+        let mut contract = group_contract(1, 1, 0, DataRequestDataType::String);
+        testing_env!(get_context(alice()));
+        contract.create_data_request(
+            U128(100),
+            payload(DataRequestDataType::String),
+            Some(U64(0)),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "only the group creator can add data requests")]
+    fn group_member_by_other_account() {
+        let mut contract = group_contract(2, 1, 0, DataRequestDataType::String);
+        testing_env!(get_context(bob()));
+        contract.create_data_request(
+            U128(100),
+            payload(DataRequestDataType::String),
+            Some(U64(0)),
+        );
+    }
+
+    #[test]
+    fn group_median() {
+        let mut contract = group_contract(4, 3, 1, DataRequestDataType::Number(U128(100)));
+        set_outcome(&mut contract, 0, number(300, false));
+        set_outcome(&mut contract, 1, number(100, true));
+        assert_eq!(contract.get_group_result(U64(0)), None);
+
+        set_outcome(&mut contract, 2, number(200, false));
+        assert_eq!(
+            contract.get_group_result(U64(0)),
+            Some(TypedOutcome::Number(FixedPoint {
+                value: U128(200),
+                multiplier: U128(100),
+                negative: false,
+            }))
+        );
+
+        // the result is frozen at quorum, the lower median of -100, 50, 200, 300 would be 50
+        set_outcome(&mut contract, 3, number(50, false));
+        assert_eq!(
+            contract.get_group_result(U64(0)),
+            Some(TypedOutcome::Number(FixedPoint {
+                value: U128(200),
+                multiplier: U128(100),
+                negative: false,
+            }))
+        );
+        assert_eq!(contract.get_request_group(U64(0)).unwrap().answers.len(), 4);
+    }
+
+    #[test]
+    fn group_majority() {
+        let mut contract = group_contract(3, 2, 1, DataRequestDataType::String);
+        set_outcome(&mut contract, 0, string("a"));
+        set_outcome(&mut contract, 1, string("b"));
+        assert_eq!(
+            contract.get_group_result(U64(0)),
+            Some(TypedOutcome::String("a".to_string()))
+        );
+
+        // "b" becoming the majority doesn't change the result reached at quorum
+        set_outcome(&mut contract, 2, string("b"));
+        assert_eq!(
+            contract.get_group_result(U64(0)),
+            Some(TypedOutcome::String("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn group_too_many_invalid() {
+        let mut contract = group_contract(3, 1, 1, DataRequestDataType::String);
+        set_outcome(&mut contract, 0, Outcome::Invalid);
+        assert_eq!(contract.get_group_result(U64(0)), None);
+
+        set_outcome(&mut contract, 1, Outcome::Invalid);
+        assert_eq!(
+            contract.get_group_result(U64(0)),
+            Some(TypedOutcome::Invalid)
+        );
+
+        set_outcome(&mut contract, 2, string("a"));
+        assert_eq!(
+            contract.get_group_result(U64(0)),
+            Some(TypedOutcome::Invalid)
+        );
+    }
+}
@@ -0,0 +1,271 @@
+/**
+ * Helpers for requester contracts that consume Flux outcomes
+ *
+ * Turns the `Outcome` received through `set_outcome` into a typed value using the data type and outcome list
+ * the data request was created with.
+ */
+use crate::helpers::calc_product;
+use flux_sdk::{
+    data_request::DataRequestDataType,
+    outcome::{AnswerType, Outcome},
+};
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::U128,
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+/// Separator of the outcome indices in the answer of a multi-select data request
+pub const SELECTION_SEPARATOR: char = ',';
+
+/// Fixed-point number, the represented value is `value / multiplier`, negated if `negative`
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FixedPoint {
+    pub value: U128,
+    pub multiplier: U128,
+    pub negative: bool,
+}
+
+impl FixedPoint {
+    /**
+     * @notice Expresses the number in another multiplier, rounding towards zero when precision is lost
+     */
+    pub fn rescale(&self, multiplier: U128) -> FixedPoint {
+        let value = calc_product(self.value.into(), multiplier.into(), self.multiplier.into());
+        FixedPoint {
+            value: U128(value),
+            multiplier,
+            negative: self.negative && value > 0,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TypedOutcome {
+    Number(FixedPoint),
+    Enum { index: usize, value: String }, // position of the answer in the data request's `outcomes`
+    // positions of the selected `outcomes` in ascending order
+    MultiSelect {
+        indices: Vec<usize>,
+        values: Vec<String>,
+    },
+    String(String),
+    Invalid,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    WrongType,       // the answer does not match the data type of the data request
+    WrongMultiplier, // the number answer has another multiplier than the data request
+    UnknownOutcome, // the answer is neither in the data request's `outcomes` nor a selection of them
+}
+
+/**
+ * @notice Decodes `outcome` against the `data_type` and `outcomes` the data request was created with
+ */
+pub fn decode_outcome(
+    outcome: &Outcome,
+    data_type: &DataRequestDataType,
+    outcomes: Option<&Vec<String>>,
+) -> Result<TypedOutcome, DecodeError> {
+    let answer = match outcome {
+        Outcome::Answer(answer) => answer,
+        Outcome::Invalid => return Ok(TypedOutcome::Invalid),
+    };
+
+    match (answer, data_type) {
+        (AnswerType::Number(number), DataRequestDataType::Number(multiplier)) => {
+            if number.multiplier != *multiplier {
+                return Err(DecodeError::WrongMultiplier);
+            }
+            Ok(TypedOutcome::Number(FixedPoint {
+                value: number.value,
+                multiplier: number.multiplier,
+                negative: number.negative,
+            }))
+        }
+        (AnswerType::String(value), DataRequestDataType::String) => match outcomes {
+            Some(outcomes) => match outcomes.iter().position(|o| o == value) {
+                Some(index) => Ok(TypedOutcome::Enum {
+                    index,
+                    value: value.to_string(),
+                }),
+                None => decode_selection(value, outcomes),
+            },
+            None => Ok(TypedOutcome::String(value.to_string())),
+        },
+        _ => Err(DecodeError::WrongType),
+    }
+}
+
+/**
+ * @notice Decodes the answer of a multi-select data request, the indices of the selected `outcomes` in ascending
+ * order separated by a comma, e.g. "0,2". An empty answer selects none
+ */
+fn decode_selection(answer: &str, outcomes: &[String]) -> Result<TypedOutcome, DecodeError> {
+    let mut indices: Vec<usize> = Vec::new();
+    if !answer.is_empty() {
+        for part in answer.split(SELECTION_SEPARATOR) {
+            let index: usize = part.parse().map_err(|_| DecodeError::UnknownOutcome)?;
+            // the oracle only accepts selections in canonical form, without leading zeros or repeated indices
+            let is_canonical = index.to_string() == part
+                && index < outcomes.len()
+                && indices.last().map(|prev| index > *prev).unwrap_or(true);
+            if !is_canonical {
+                return Err(DecodeError::UnknownOutcome);
+            }
+            indices.push(index);
+        }
+    }
+
+    Ok(TypedOutcome::MultiSelect {
+        values: indices
+            .iter()
+            .map(|index| outcomes[*index].to_string())
+            .collect(),
+        indices,
+    })
+}
+
+/**
+ * @notice Checks whether `set_outcome` was called by `oracle`. Uses the predecessor rather than the signer, which is
+ * whoever finalized the data request, so the check holds however the oracle's call was triggered
+ */
+pub fn is_from_oracle(oracle: &AccountId) -> bool {
+    &env::predecessor_account_id() == oracle
+}
+
+pub fn assert_from_oracle(oracle: &AccountId) {
+    assert!(
+        is_from_oracle(oracle),
+        "This method is only callable by the oracle {}",
+        oracle
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use flux_sdk::outcome::AnswerNumberType;
+
+    fn number(value: u128, multiplier: u128, negative: bool) -> Outcome {
+        Outcome::Answer(AnswerType::Number(AnswerNumberType {
+            value: U128(value),
+            multiplier: U128(multiplier),
+            negative,
+        }))
+    }
+
+    fn string(value: &str) -> Outcome {
+        Outcome::Answer(AnswerType::String(value.to_string()))
+    }
+
+    #[test]
+    fn decode_number() {
+        assert_eq!(
+            decode_outcome(
+                &number(12345, 100, true),
+                &DataRequestDataType::Number(U128(100)),
+                None
+            ),
+            Ok(TypedOutcome::Number(FixedPoint {
+                value: U128(12345),
+                multiplier: U128(100),
+                negative: true,
+            }))
+        );
+    }
+
+    #[test]
+    fn decode_number_wrong_multiplier() {
+        assert_eq!(
+            decode_outcome(
+                &number(12345, 10, false),
+                &DataRequestDataType::Number(U128(100)),
+                None
+            ),
+            Err(DecodeError::WrongMultiplier)
+        );
+    }
+
+    #[test]
+    fn decode_enum() {
+        let outcomes = vec!["yes".to_string(), "no".to_string()];
+        assert_eq!(
+            decode_outcome(&string("no"), &DataRequestDataType::String, Some(&outcomes)),
+            Ok(TypedOutcome::Enum {
+                index: 1,
+                value: "no".to_string()
+            })
+        );
+        assert_eq!(
+            decode_outcome(&string("maybe"), &DataRequestDataType::String, Some(&outcomes)),
+            Err(DecodeError::UnknownOutcome)
+        );
+    }
+
+    #[test]
+    fn decode_multi_select() {
+        let outcomes = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert_eq!(
+            decode_outcome(
+                &string("0,2"),
+                &DataRequestDataType::String,
+                Some(&outcomes)
+            ),
+            Ok(TypedOutcome::MultiSelect {
+                indices: vec![0, 2],
+                values: vec!["a".to_string(), "c".to_string()]
+            })
+        );
+        assert_eq!(
+            decode_outcome(&string(""), &DataRequestDataType::String, Some(&outcomes)),
+            Ok(TypedOutcome::MultiSelect {
+                indices: vec![],
+                values: vec![]
+            })
+        );
+        for answer in &["2,0", "0,3", "0,,1", "01"] {
+            assert_eq!(
+                decode_outcome(
+                    &string(answer),
+                    &DataRequestDataType::String,
+                    Some(&outcomes)
+                ),
+                Err(DecodeError::UnknownOutcome)
+            );
+        }
+    }
+
+    #[test]
+    fn decode_string_and_invalid() {
+        assert_eq!(
+            decode_outcome(&string("a"), &DataRequestDataType::String, None),
+            Ok(TypedOutcome::String("a".to_string()))
+        );
+        assert_eq!(
+            decode_outcome(&string("a"), &DataRequestDataType::Number(U128(10)), None),
+            Err(DecodeError::WrongType)
+        );
+        assert_eq!(
+            decode_outcome(&Outcome::Invalid, &DataRequestDataType::String, None),
+            Ok(TypedOutcome::Invalid)
+        );
+    }
+
+    #[test]
+    fn rescale() {
+        let price = FixedPoint {
+            value: U128(12345),
+            multiplier: U128(100),
+            negative: false,
+        };
+        assert_eq!(price.rescale(U128(10)).value, U128(1234));
+        assert_eq!(price.rescale(U128(1000)).value, U128(123450));
+    }
+}
@@ -0,0 +1,420 @@
+use crate::fungible_token::{fungible_token_transfer_call, GAS_BASE_TRANSFER_CALL};
+use crate::*;
+use flux_sdk::{
+    config::OracleConfig,
+    data_request::{DataRequestDataType, NewDataRequestArgs},
+    outcome::Outcome,
+    types::WrappedBalance,
+};
+use near_sdk::{
+    ext_contract,
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+    serde_json::{self, json},
+    Gas, Promise, PromiseResult,
+};
+
+/// Gas for fetching the oracle's config
+const GAS_BASE_GET_CONFIG: Gas = 10_000_000_000_000;
+/// Gas for the callback that handles the result of the `ft_transfer_call` that created the data request
+const GAS_BASE_RESOLVE_DR_NEW: Gas = 10_000_000_000_000;
+/// Gas for the callback that receives the oracle's config and creates the data request
+const GAS_BASE_PROCEED_DR_NEW: Gas =
+    GAS_BASE_TRANSFER_CALL + GAS_BASE_RESOLVE_DR_NEW + 10_000_000_000_000;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ValidityBondStatus {
+    Pending,   // the data request is being created
+    Locked,    // held by the oracle until the data request is finalized
+    Returned,  // returned by the oracle because the data request resolved to a valid answer
+    Forfeited, // kept by the oracle because the data request resolved as invalid after being staked on
+}
+
+/// A data request this contract created on the oracle
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DataRequestDetails {
+    pub nonce: U64,
+    pub creator: AccountId,
+    pub amount: WrappedBalance, // tokens sent along with the request, anything unused is refunded by the oracle
+    pub validity_bond: WrappedBalance,
+    pub validity_bond_status: ValidityBondStatus,
+    pub tags: Vec<String>, // tags as given by the creator, without the nonce tag
+    pub data_type: DataRequestDataType,
+    pub outcomes: Option<Vec<String>>,
+    pub outcome: Option<Outcome>,
+    pub group: Option<U64>,
+}
+
+#[ext_contract(ext_oracle)]
+trait OracleExt {
+    fn get_config(&self) -> OracleConfig;
+}
+
+#[ext_contract(ext_self)]
+trait SelfExt {
+    fn proceed_dr_new(nonce: U64, amount: U128, payload: NewDataRequestArgs) -> Promise;
+    fn resolve_dr_new(nonce: U64) -> bool;
+}
+
+#[near_bindgen]
+impl RequesterContract {
+    /**
+     * @notice Creates a data request on the oracle paid for with `amount` of this contract's payment tokens.
+     * The nonce of the request is appended to its tags so the outcome can be matched once `set_outcome` is called
+     * @param group_id optional group of which the outcomes are aggregated, its tag is added to the request's tags
+     * @returns the nonce of the data request
+     */
+    pub fn create_data_request(
+        &mut self,
+        amount: U128,
+        mut payload: NewDataRequestArgs,
+        group_id: Option<U64>,
+    ) -> U64 {
+        let creator = env::predecessor_account_id();
+        self.assert_whitelisted(&creator);
+
+        let nonce = self.nonce;
+        self.nonce += 1;
+        if let Some(group_id) = group_id {
+            let tag = self.add_group_member(
+                group_id,
+                nonce,
+                &creator,
+                &payload.data_type,
+                &payload.outcomes,
+            );
+            payload.tags.push(tag);
+        }
+        self.data_requests.insert(
+            &nonce,
+            &DataRequestDetails {
+                nonce: U64(nonce),
+                creator,
+                amount,
+                validity_bond: U128(0),
+                validity_bond_status: ValidityBondStatus::Pending,
+                tags: payload.tags.clone(),
+                data_type: payload.data_type.clone(),
+                outcomes: payload.outcomes.clone(),
+                outcome: None,
+                group: group_id,
+            },
+        );
+
+        ext_oracle::get_config(
+            // NEAR params
+            &self.oracle,
+            0,
+            GAS_BASE_GET_CONFIG,
+        )
+        .then(ext_self::proceed_dr_new(
+            U64(nonce),
+            amount,
+            payload,
+            // NEAR params
+            &env::current_account_id(),
+            0,
+            GAS_BASE_PROCEED_DR_NEW,
+        ));
+
+        U64(nonce)
+    }
+
+    /**
+     * @notice Records the validity bond of the oracle's current config and transfers the request to the oracle
+     */
+    #[private]
+    pub fn proceed_dr_new(
+        &mut self,
+        nonce: U64,
+        amount: U128,
+        mut payload: NewDataRequestArgs,
+    ) -> Promise {
+        let config: OracleConfig = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice(&value).expect("ERR_INVALID_ORACLE_CONFIG")
+            }
+            _ => env::panic(b"ERR_GET_ORACLE_CONFIG_FAILED"),
+        };
+
+        let mut dr = self.data_requests.get(&nonce.into()).unwrap();
+        dr.validity_bond = config.validity_bond;
+        self.data_requests.insert(&nonce.into(), &dr);
+
+        payload.tags.push(u64::from(nonce).to_string());
+        fungible_token_transfer_call(
+            self.payment_token.to_string(),
+            self.oracle.to_string(),
+            amount.into(),
+            json!({ "NewDataRequest": payload }).to_string(),
+        )
+        .then(ext_self::resolve_dr_new(
+            nonce,
+            // NEAR params
+            &env::current_account_id(),
+            0,
+            GAS_BASE_RESOLVE_DR_NEW,
+        ))
+    }
+
+    /**
+     * @notice Locks the validity bond if the oracle accepted the data request, forgets the request otherwise
+     * @returns whether the data request was created
+     */
+    #[private]
+    pub fn resolve_dr_new(&mut self, nonce: U64) -> bool {
+        let used = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                .map(u128::from)
+                .unwrap_or(0),
+            _ => 0,
+        };
+
+        if used == 0 {
+            let dr = self.data_requests.remove(&nonce.into()).unwrap();
+            if let Some(group_id) = dr.group {
+                self.remove_group_member(group_id, nonce.into());
+            }
+            return false;
+        }
+
+        let mut dr = self.data_requests.get(&nonce.into()).unwrap();
+        dr.validity_bond_status = ValidityBondStatus::Locked;
+        self.data_requests.insert(&nonce.into(), &dr);
+        true
+    }
+
+    /**
+     * @notice Called by the oracle once a data request created by this contract is finalized
+     * @param validity_bond_returned whether the oracle returned the validity bond, which it also does for requests
+     * that expired as Invalid because nobody staked on them
+     */
+    pub fn set_outcome(
+        &mut self,
+        requester: AccountId,
+        outcome: Outcome,
+        tags: Vec<String>,
+        validity_bond_returned: bool,
+    ) {
+        self.assert_oracle();
+        assert_eq!(
+            requester,
+            env::current_account_id(),
+            "outcome is meant for another requester"
+        );
+
+        let nonce: u64 = tags
+            .last()
+            .and_then(|tag| tag.parse().ok())
+            .expect("ERR_NO_NONCE_TAG");
+        let mut dr = self
+            .data_requests
+            .get(&nonce)
+            .expect("ERR_UNKNOWN_DATA_REQUEST");
+        // a second delivery would be counted twice in the request's group
+        assert!(dr.outcome.is_none(), "ERR_OUTCOME_ALREADY_SET");
+
+        dr.validity_bond_status = if validity_bond_returned {
+            ValidityBondStatus::Returned
+        } else {
+            ValidityBondStatus::Forfeited
+        };
+        dr.outcome = Some(outcome.clone());
+        self.outcomes_by_tags.insert(&dr.tags, &outcome);
+        self.data_requests.insert(&nonce, &dr);
+
+        if let Some(group_id) = dr.group {
+            self.record_group_outcome(group_id, &outcome);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod mock_token_basic_tests {
+    use super::*;
+    use crate::consumer::TypedOutcome;
+    use flux_sdk::outcome::AnswerType;
+    use near_sdk::{testing_env, MockedBlockchain, VMContext};
+
+    fn alice() -> AccountId {
+        "alice.near".to_string()
+    }
+
+    fn oracle() -> AccountId {
+        "oracle.near".to_string()
+    }
+
+    fn token() -> AccountId {
+        "token.near".to_string()
+    }
+
+    fn requester() -> AccountId {
+        "requester.near".to_string()
+    }
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContext {
+        VMContext {
+            current_account_id: requester(),
+            signer_account_id: alice(),
+            signer_account_pk: vec![0, 1, 2],
+            predecessor_account_id,
+            input: vec![],
+            block_index: 0,
+            block_timestamp: 0,
+            account_balance: 10000 * 10u128.pow(24),
+            account_locked_balance: 0,
+            storage_usage: 10u64.pow(6),
+            attached_deposit: 0,
+            prepaid_gas: 10u64.pow(18),
+            random_seed: vec![0, 1, 2],
+            is_view: false,
+            output_data_receivers: vec![],
+            epoch_height: 0,
+        }
+    }
+
+    fn payload() -> NewDataRequestArgs {
+        NewDataRequestArgs {
+            sources: Some(Vec::new()),
+            outcomes: None,
+            challenge_period: U64(1000),
+            description: Some("a".to_string()),
+            tags: vec!["price".to_string()],
+            data_type: DataRequestDataType::String,
+            provider: None,
+        }
+    }
+
+    fn answer() -> Outcome {
+        Outcome::Answer(AnswerType::String("a".to_string()))
+    }
+
+    fn created_contract() -> RequesterContract {
+        testing_env!(get_context(alice()));
+        let mut contract = RequesterContract::new(oracle(), token(), None);
+        assert_eq!(contract.create_data_request(U128(100), payload(), None), U64(0));
+        contract
+    }
+
+    #[test]
+    fn create_data_request() {
+        let contract = created_contract();
+        let dr = contract.get_data_request(U64(0)).unwrap();
+        assert_eq!(dr.creator, alice());
+        assert_eq!(dr.amount, U128(100));
+        assert_eq!(dr.tags, vec!["price".to_string()]);
+        assert_eq!(dr.validity_bond_status, ValidityBondStatus::Pending);
+        assert_eq!(contract.nonce, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Err predecessor is not whitelisted")]
+    fn create_data_request_not_whitelisted() {
+        testing_env!(get_context(alice()));
+        let mut contract = RequesterContract::new(oracle(), token(), Some(vec![oracle()]));
+        contract.create_data_request(U128(100), payload(), None);
+    }
+
+    #[test]
+    fn set_outcome() {
+        let mut contract = created_contract();
+        testing_env!(get_context(oracle()));
+        contract.set_outcome(
+            requester(),
+            answer(),
+            vec!["price".to_string(), "0".to_string()],
+            true,
+        );
+
+        assert_eq!(contract.get_outcome(U64(0)), Some(answer()));
+        assert_eq!(
+            contract.get_typed_outcome(U64(0)),
+            Some(TypedOutcome::String("a".to_string()))
+        );
+        assert_eq!(
+            contract.get_outcome_by_tags(vec!["price".to_string()]),
+            Some(answer())
+        );
+        assert_eq!(
+            contract
+                .get_data_request(U64(0))
+                .unwrap()
+                .validity_bond_status,
+            ValidityBondStatus::Returned
+        );
+    }
+
+    #[test]
+    fn set_outcome_invalid() {
+        let mut contract = created_contract();
+        testing_env!(get_context(oracle()));
+        contract.set_outcome(
+            requester(),
+            Outcome::Invalid,
+            vec!["price".to_string(), "0".to_string()],
+            false,
+        );
+
+        assert_eq!(
+            contract
+                .get_data_request(U64(0))
+                .unwrap()
+                .validity_bond_status,
+            ValidityBondStatus::Forfeited
+        );
+    }
+
+    #[test]
+    fn set_outcome_expired() {
+        let mut contract = created_contract();
+        testing_env!(get_context(oracle()));
+        contract.set_outcome(
+            requester(),
+            Outcome::Invalid,
+            vec!["price".to_string(), "0".to_string()],
+            true,
+        );
+
+        assert_eq!(
+            contract
+                .get_data_request(U64(0))
+                .unwrap()
+                .validity_bond_status,
+            ValidityBondStatus::Returned
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_OUTCOME_ALREADY_SET")]
+    fn set_outcome_twice() {
+        let mut contract = created_contract();
+        testing_env!(get_context(oracle()));
+        let tags = vec!["price".to_string(), "0".to_string()];
+        contract.set_outcome(requester(), answer(), tags.clone(), true);
+        contract.set_outcome(requester(), answer(), tags, true);
+    }
+
+    #[test]
+    #[should_panic(expected = "Err predecessor is not whitelisted")]
+    fn create_data_request_not_owner() {
+        testing_env!(get_context(alice()));
+        let mut contract = RequesterContract::new(oracle(), token(), None);
+        testing_env!(get_context(oracle()));
+        contract.create_data_request(U128(100), payload(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "This method is only callable by the oracle oracle.near")]
+    fn set_outcome_not_oracle() {
+        let mut contract = created_contract();
+        contract.set_outcome(
+            requester(),
+            answer(),
+            vec!["price".to_string(), "0".to_string()],
+            true,
+        );
+    }
+}
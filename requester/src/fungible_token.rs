@@ -0,0 +1,33 @@
+use near_sdk::{ext_contract, json_types::U128, AccountId, Gas, Promise};
+
+/// Gas attached to `ft_transfer_call`, enough for the token and the oracle's `ft_on_transfer`
+pub const GAS_BASE_TRANSFER_CALL: Gas = 150_000_000_000_000;
+
+#[ext_contract]
+pub trait FungibleToken {
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> U128;
+}
+
+pub fn fungible_token_transfer_call(
+    token_account_id: AccountId,
+    receiver_id: AccountId,
+    value: u128,
+    msg: String,
+) -> Promise {
+    fungible_token::ft_transfer_call(
+        receiver_id,
+        U128(value),
+        None,
+        msg,
+        // NEAR params
+        &token_account_id,
+        1,
+        GAS_BASE_TRANSFER_CALL,
+    )
+}
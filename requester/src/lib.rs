@@ -0,0 +1,115 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    collections::{LookupMap, LookupSet},
+    env,
+    json_types::U64,
+    near_bindgen, AccountId,
+};
+
+near_sdk::setup_alloc!();
+
+pub mod aggregation;
+pub mod consumer;
+pub mod data_request;
+mod fungible_token;
+mod helpers;
+
+use aggregation::RequestGroup;
+use consumer::TypedOutcome;
+use data_request::DataRequestDetails;
+use flux_sdk::outcome::Outcome;
+
+/**
+ * Reference requester contract
+ *
+ * Creates data requests on the oracle through `ft_transfer_call`, tracks the validity bond it put up for each of them
+ * and stores the outcomes it receives through `set_outcome` by request and by tags.
+ */
+#[near_bindgen]
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RequesterContract {
+    pub oracle: AccountId,
+    pub payment_token: AccountId,
+    pub whitelist: LookupSet<AccountId>, // accounts allowed to create data requests paid for by this contract
+    pub nonce: u64,
+    pub data_requests: LookupMap<u64, DataRequestDetails>,
+    pub outcomes_by_tags: LookupMap<Vec<String>, Outcome>, // latest outcome received for the tags a request was created with
+    pub group_nonce: u64,
+    pub groups: LookupMap<u64, RequestGroup>,
+}
+
+impl Default for RequesterContract {
+    fn default() -> Self {
+        env::panic(b"Contract should be initialized before usage")
+    }
+}
+
+#[near_bindgen]
+impl RequesterContract {
+    /**
+     * @param whitelist accounts allowed to create data requests, only the account initializing the contract if `None`
+     * since every request is paid for with this contract's tokens
+     */
+    #[init]
+    pub fn new(
+        oracle: AccountId,
+        payment_token: AccountId,
+        whitelist: Option<Vec<AccountId>>,
+    ) -> Self {
+        assert!(!env::state_exists(), "Already initialized");
+        let mut set = LookupSet::new(b"w".to_vec());
+        let accounts = whitelist.unwrap_or_else(|| vec![env::predecessor_account_id()]);
+        for account in accounts.iter() {
+            set.insert(account);
+        }
+
+        Self {
+            oracle,
+            payment_token,
+            whitelist: set,
+            nonce: 0,
+            data_requests: LookupMap::new(b"dr".to_vec()),
+            outcomes_by_tags: LookupMap::new(b"ot".to_vec()),
+            group_nonce: 0,
+            groups: LookupMap::new(b"g".to_vec()),
+        }
+    }
+
+    pub fn get_data_request(&self, nonce: U64) -> Option<DataRequestDetails> {
+        self.data_requests.get(&nonce.into())
+    }
+
+    pub fn get_outcome(&self, nonce: U64) -> Option<Outcome> {
+        self.data_requests
+            .get(&nonce.into())
+            .and_then(|dr| dr.outcome)
+    }
+
+    /**
+     * @returns the outcome of a data request decoded against the data type and outcomes it was created with, `None`
+     * if it has no outcome yet or the outcome can't be decoded, in which case `get_outcome` still returns it
+     */
+    pub fn get_typed_outcome(&self, nonce: U64) -> Option<TypedOutcome> {
+        let dr = self.data_requests.get(&nonce.into())?;
+        dr.outcome.as_ref().and_then(|outcome| {
+            consumer::decode_outcome(outcome, &dr.data_type, dr.outcomes.as_ref()).ok()
+        })
+    }
+
+    pub fn get_outcome_by_tags(&self, tags: Vec<String>) -> Option<Outcome> {
+        self.outcomes_by_tags.get(&tags)
+    }
+}
+
+impl RequesterContract {
+    pub fn assert_oracle(&self) {
+        consumer::assert_from_oracle(&self.oracle);
+    }
+
+    pub fn assert_whitelisted(&self, account_id: &AccountId) {
+        assert!(
+            self.whitelist.contains(account_id),
+            "Err predecessor is not whitelisted"
+        );
+    }
+}